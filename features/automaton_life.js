@@ -0,0 +1,5 @@
+x();
+render();
+all();
+automaton('B3/S23');
+render();
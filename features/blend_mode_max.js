@@ -0,0 +1,5 @@
+check();
+render();
+blendMode('max');
+x();
+render();
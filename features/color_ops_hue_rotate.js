@@ -0,0 +1,4 @@
+rotateColor('red', 0.5 * TAU);
+colorOps(['hueRotate', TAU / 3]);
+x();
+render();
@@ -0,0 +1,11 @@
+x();
+render();
+all();
+identity();
+curves({
+  luminance: [
+    [0, 1],
+    [1, 0],
+  ],
+});
+render();
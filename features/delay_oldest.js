@@ -0,0 +1,17 @@
+rewindCapacity(3);
+
+x();
+await render();
+await frame();
+
+all();
+await render();
+await frame();
+
+x();
+await render();
+await frame();
+
+delay(10);
+all();
+render();
@@ -0,0 +1,4 @@
+coordinates(true);
+dither('bayer');
+threshold(0.5);
+render();
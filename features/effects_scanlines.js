@@ -0,0 +1,2 @@
+effects([{ scanlines: { intensity: 1, spacing: 1 } }]);
+render();
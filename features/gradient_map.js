@@ -0,0 +1,9 @@
+x();
+render();
+palette([
+  [1, 0, 0],
+  [0, 0, 1],
+]);
+gradientMap();
+all();
+render();
@@ -0,0 +1,5 @@
+x();
+render();
+all();
+kernel([0, 0, 0, 0, 0, 1, 0, 0, 0]);
+render();
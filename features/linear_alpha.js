@@ -0,0 +1,3 @@
+linear();
+alpha(0.25);
+render();
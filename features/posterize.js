@@ -0,0 +1,3 @@
+coordinates(true);
+posterize(4);
+render();
@@ -0,0 +1,3 @@
+precision('float');
+x();
+render();
@@ -0,0 +1,3 @@
+precision('half');
+x();
+render();
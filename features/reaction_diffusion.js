@@ -0,0 +1,6 @@
+x();
+render();
+all();
+identity();
+reactionDiffusion(0.055, 0.062, 0, 0);
+render();
@@ -0,0 +1,19 @@
+rewindCapacity(3);
+
+x();
+await render();
+await frame();
+
+all();
+await render();
+await frame();
+
+await render();
+await frame();
+
+x();
+await render();
+await frame();
+
+rewind(5);
+stepForward();
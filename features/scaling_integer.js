@@ -0,0 +1,4 @@
+resolution(96);
+scaling('integer');
+check();
+render();
@@ -0,0 +1,7 @@
+coordinates(true);
+render();
+coordinates(false);
+identity();
+sort('right');
+times(256);
+render();
@@ -0,0 +1,3 @@
+coordinates(true);
+threshold(0.5);
+render();
@@ -29,12 +29,12 @@ fn kaleidoscope(frame: Frame) {
   let s = 1.0 / 0.75;
   Filter::new()
     .circle()
-    .color(Rotation3::from_axis_angle(&Vector3::y_axis(), 0.05 * TAU))
+    .color(ColorTransform::rotate_green(0.05 * TAU))
     .position(Similarity2::from_scaling(s))
     .wrap(true)
     .times(8)
     .render()
-    .color(Rotation3::from_axis_angle(&Vector3::z_axis(), 0.05 * TAU))
+    .color(ColorTransform::rotate_blue(0.05 * TAU))
     .position(Similarity2::from_parts(
       Translation2::identity(),
       Rotation2::new(5.0 / 6.0 * TAU + frame.time / 30000.0 * TAU).into(),
@@ -46,12 +46,12 @@ fn kaleidoscope(frame: Frame) {
 fn orbs(frame: Frame) {
   Filter::new()
     .circle()
-    .color(Rotation3::from_axis_angle(&Vector3::y_axis(), 0.05 * TAU))
+    .color(ColorTransform::rotate_green(0.05 * TAU))
     .position(Similarity2::from_scaling(1.0 / 0.75))
     .wrap(true)
     .times(8)
     .render()
-    .color(Rotation3::from_axis_angle(&Vector3::z_axis(), 0.05 * TAU))
+    .color(ColorTransform::rotate_blue(0.05 * TAU))
     .render();
 }
 
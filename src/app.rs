@@ -5,6 +5,7 @@ pub(crate) struct App {
   animation_frame_callback: Option<Closure<dyn FnMut(f64)>>,
   aside: HtmlElement,
   audio_context: AudioContext,
+  dirty: bool,
   document: Document,
   gpu: Gpu,
   html: HtmlElement,
@@ -15,6 +16,7 @@ pub(crate) struct App {
   run_button: HtmlButtonElement,
   select: HtmlSelectElement,
   share_button: HtmlButtonElement,
+  size: (u32, u32),
   started: bool,
   stderr: Stderr,
   textarea: HtmlTextAreaElement,
@@ -128,6 +130,7 @@ impl App {
       animation_frame_callback: None,
       aside: document.select::<HtmlElement>("aside")?,
       audio_context,
+      dirty: false,
       document,
       gpu,
       html,
@@ -138,6 +141,7 @@ impl App {
       run_button: run_button.clone(),
       select: select.clone(),
       share_button: share_button.clone(),
+      size: (0, 0),
       started: false,
       stderr: stderr.clone(),
       textarea: textarea.clone(),
@@ -163,9 +167,25 @@ impl App {
 
     if loader {
       Self::add_event_listener(&app, &body, "click", move |app| app.on_beat())?;
-      Self::add_event_listener(&app, &body, "keydown", move |app| app.on_beat())?;
+      Self::add_event_listener_with_event(&app, &body, "keydown", move |app, event| {
+        app.on_key(event)
+      })?;
     }
 
+    Self::add_event_listener_with_event(
+      &app,
+      &canvas,
+      "webglcontextlost",
+      move |_, event: web_sys::Event| {
+        event.prevent_default();
+        Ok(())
+      },
+    )?;
+
+    Self::add_event_listener(&app, &canvas, "webglcontextrestored", move |app| {
+      app.gpu.restore()
+    })?;
+
     Self::add_event_listener(&app, &textarea, "input", move |app| app.on_input())?;
 
     Self::add_event_listener_with_event(&app, &textarea, "keydown", move |app, event| {
@@ -268,8 +288,30 @@ impl App {
   fn on_animation_frame(&mut self, timestamp: f64) -> Result {
     self.request_animation_frame()?;
 
+    if self.gpu.context_lost() {
+      return Ok(());
+    }
+
     self.gpu.resize()?;
 
+    self.present()?;
+
+    let size = self.gpu.size();
+
+    if size != self.size {
+      self.size = size;
+      let (width, height) = size;
+      self
+        .worker
+        .post_message(&JsValue::from_str(&serde_json::to_string(
+          &Event::Resize { width, height },
+        )?))?;
+    }
+
+    self.gpu.sample_audio()?;
+
+    self.gpu.capture()?;
+
     self
       .worker
       .post_message(&JsValue::from_str(&serde_json::to_string(&Event::Frame(
@@ -279,6 +321,18 @@ impl App {
     Ok(())
   }
 
+  // Renders are presented at most once per animation frame, since presenting
+  // with effects is expensive, and when the script is done, so that its final
+  // output is visible.
+  fn present(&mut self) -> Result {
+    if self.dirty && !self.gpu.context_lost() {
+      self.gpu.present()?;
+      self.dirty = false;
+    }
+
+    Ok(())
+  }
+
   fn on_message(&mut self, event: MessageEvent) -> Result {
     let json = event
       .data()
@@ -290,14 +344,21 @@ impl App {
 
     match event {
       Message::Clear => {
-        self.gpu.clear()?;
+        if !self.gpu.context_lost() {
+          self.gpu.clear()?;
+        }
       }
       Message::DecibelRange { min, max } => {
         self.gpu.set_decibel_range(min, max);
       }
       Message::Done => {
+        self.present()?;
         self.html.set_class_name("done");
       }
+      Message::Effects(effects) => {
+        self.gpu.set_effects(effects)?;
+        self.gpu.present()?;
+      }
       Message::Error(error) => {
         self.stderr.update(Err(error.into()));
       }
@@ -328,25 +389,58 @@ impl App {
         }
       }
       Message::Render(filter) => {
-        self.gpu.render(&filter)?;
-        self.gpu.present()?;
+        if !self.gpu.context_lost() {
+          self.gpu.render(&filter)?;
+          self.dirty = true;
+        }
+      }
+      Message::Palette(palette) => {
+        self.gpu.set_palette(palette)?;
+      }
+      Message::Precision(precision) => {
+        self.gpu.set_precision(precision)?;
       }
       Message::Resolution(resolution) => {
-        self.gpu.lock_resolution(resolution);
+        self.gpu.lock_resolution(resolution)?;
+      }
+      Message::RestoreRewind => {
+        if !self.gpu.context_lost() {
+          self.gpu.restore_rewind()?;
+        }
+      }
+      Message::Rewind(position) => {
+        self.gpu.rewind(position.try_into()?)?;
+      }
+      Message::RewindCapacity(capacity) => {
+        self.gpu.set_rewind_capacity(capacity)?;
       }
       Message::Save => {
-        let image = self.gpu.save_image()?;
-        let mut png = Cursor::new(Vec::new());
-        image.write_to(&mut png, ImageOutputFormat::Png)?;
-        let a = self
-          .document
-          .create_element("a")?
-          .cast::<HtmlAnchorElement>()?;
-        a.set_download("degenerate.png");
-        let mut href = String::from("data:image/png;base64,");
-        base64::encode_config_buf(png.get_ref(), base64::STANDARD, &mut href);
-        a.set_href(&href);
-        a.click();
+        if !self.gpu.context_lost() {
+          let image = self.gpu.save_image()?;
+          self.download(&image)?;
+          self.gpu.set_snapshot(image);
+        }
+      }
+      Message::SaveTiled(resolution) => {
+        if !self.gpu.context_lost() {
+          let image = self.gpu.save_tiled_image(resolution)?;
+          self.download(&image)?;
+        }
+      }
+      Message::Scaling(scaling) => {
+        self.gpu.set_scaling(scaling)?;
+      }
+      Message::Snapshot => {
+        if !self.gpu.context_lost() {
+          let image = self.gpu.save_image()?;
+          self.gpu.set_snapshot(image);
+        }
+      }
+      Message::StepBack => {
+        self.gpu.step_back()?;
+      }
+      Message::StepForward => {
+        self.gpu.step_forward()?;
       }
       Message::Widget { name, widget } => {
         let id = widget.id(&name);
@@ -483,6 +577,27 @@ impl App {
     Ok(())
   }
 
+  fn download(&self, image: &ImageBuffer<image::Rgba<u8>, Vec<u8>>) -> Result {
+    let mut png = Cursor::new(Vec::new());
+    image.write_to(&mut png, ImageOutputFormat::Png)?;
+    let a = self
+      .document
+      .create_element("a")?
+      .cast::<HtmlAnchorElement>()?;
+    a.set_download("degenerate.png");
+    let options = BlobPropertyBag::new();
+    options.set_type("image/png");
+    let blob = Blob::new_with_u8_array_sequence_and_options(
+      &Array::of1(&Uint8Array::from(png.get_ref().as_slice())),
+      &options,
+    )?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    a.set_href(&url);
+    a.click();
+    Url::revoke_object_url(&url)?;
+    Ok(())
+  }
+
   fn on_get_user_media(&mut self, media_stream: JsValue) -> Result {
     let media_stream = media_stream.cast::<MediaStream>()?;
 
@@ -545,6 +660,16 @@ impl App {
     Ok(())
   }
 
+  fn on_key(&mut self, event: KeyboardEvent) -> Result {
+    self.on_beat()?;
+    self
+      .worker
+      .post_message(&JsValue::from_str(&serde_json::to_string(&Event::Key(
+        event.key(),
+      ))?))?;
+    Ok(())
+  }
+
   fn on_input(&mut self) -> Result {
     self.start()?;
     Ok(())
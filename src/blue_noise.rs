@@ -0,0 +1,89 @@
+use super::*;
+
+// Generate a `size`×`size` blue noise threshold map using the void-and-cluster
+// method. Each pixel is assigned a distinct rank, such that pixels with
+// consecutive ranks are spread as evenly as possible, and the map wraps
+// seamlessly. Ranks are returned scaled to `[0, 255]`.
+pub(crate) fn blue_noise(size: usize) -> Vec<u8> {
+  const SIGMA: f32 = 1.5;
+
+  let len = size * size;
+
+  // Precompute the energy contribution of a pixel at each toroidal offset
+  let mut kernel = vec![0.0; len];
+  for y in 0..size {
+    for x in 0..size {
+      let dx = x.min(size - x) as f32;
+      let dy = y.min(size - y) as f32;
+      kernel[y * size + x] = (-(dx * dx + dy * dy) / (2.0 * SIGMA * SIGMA)).exp();
+    }
+  }
+
+  let mut pattern = vec![false; len];
+  let mut energy = vec![0.0; len];
+
+  let toggle = |pattern: &mut [bool], energy: &mut [f32], i: usize| {
+    pattern[i] = !pattern[i];
+    let sign = if pattern[i] { 1.0 } else { -1.0 };
+    let (x0, y0) = (i % size, i / size);
+    for y in 0..size {
+      for x in 0..size {
+        let offset = ((y + size - y0) % size) * size + (x + size - x0) % size;
+        energy[y * size + x] += sign * kernel[offset];
+      }
+    }
+  };
+
+  // The tightest cluster is the set pixel with the most energy, and the
+  // largest void is the unset pixel with the least energy
+  let tightest_cluster = |pattern: &[bool], energy: &[f32]| {
+    (0..len)
+      .filter(|&i| pattern[i])
+      .max_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+  };
+
+  let largest_void = |pattern: &[bool], energy: &[f32]| {
+    (0..len)
+      .filter(|&i| !pattern[i])
+      .min_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+  };
+
+  // Create an initial pattern of evenly spread pixels by repeatedly filling
+  // the largest void
+  let initial = (len / 10).max(1);
+  while let Some(i) = largest_void(&pattern, &energy) {
+    if pattern.iter().filter(|&&set| set).count() == initial {
+      break;
+    }
+    toggle(&mut pattern, &mut energy, i);
+  }
+
+  let prototype = pattern.clone();
+  let prototype_energy = energy.clone();
+
+  let mut rank = vec![0; len];
+
+  // Rank pixels in the initial pattern by repeatedly removing the tightest
+  // cluster
+  let mut ones = initial;
+  while let Some(i) = tightest_cluster(&pattern, &energy) {
+    ones -= 1;
+    rank[i] = ones;
+    toggle(&mut pattern, &mut energy, i);
+  }
+
+  // Rank the remaining pixels by repeatedly filling the largest void
+  pattern = prototype;
+  energy = prototype_energy;
+  let mut ones = initial;
+  while let Some(i) = largest_void(&pattern, &energy) {
+    rank[i] = ones;
+    ones += 1;
+    toggle(&mut pattern, &mut energy, i);
+  }
+
+  rank
+    .into_iter()
+    .map(|rank| (rank * 256 / len) as u8)
+    .collect()
+}
@@ -0,0 +1,153 @@
+use super::*;
+
+// Color transforms operate on color vectors with components in [-1, 1],
+// where zero is middle gray, and are applied to colors after they are sampled.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ColorTransform(pub Matrix4);
+
+impl ColorTransform {
+  const LUMINANCE: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+  pub fn identity() -> Self {
+    Self(Matrix4::identity())
+  }
+
+  pub fn invert() -> Self {
+    Self(Similarity3::from_scaling(-1.0).into())
+  }
+
+  pub fn rotate(axis: Vector3, angle: f32) -> Self {
+    Self(Rotation3::from_axis_angle(&nalgebra::Unit::new_normalize(axis), angle).into())
+  }
+
+  pub fn rotate_red(angle: f32) -> Self {
+    Self::rotate(Vector3::x(), angle)
+  }
+
+  pub fn rotate_green(angle: f32) -> Self {
+    Self::rotate(Vector3::y(), angle)
+  }
+
+  pub fn rotate_blue(angle: f32) -> Self {
+    Self::rotate(Vector3::z(), angle)
+  }
+
+  pub fn hue(angle: f32) -> Self {
+    Self::rotate(Vector3::new(1.0, 1.0, 1.0), angle)
+  }
+
+  pub fn saturation(saturation: f32) -> Self {
+    let mut mixer = [[0.0; 3]; 3];
+    for (row, weights) in mixer.iter_mut().enumerate() {
+      for (column, weight) in weights.iter_mut().enumerate() {
+        *weight = (1.0 - saturation) * Self::LUMINANCE[column];
+        if row == column {
+          *weight += saturation;
+        }
+      }
+    }
+    Self::mixer(mixer)
+  }
+
+  pub fn brightness(brightness: f32) -> Self {
+    Self(Matrix4::new_translation(&Vector3::repeat(brightness * 2.0)))
+  }
+
+  pub fn contrast(contrast: f32) -> Self {
+    Self(Matrix4::new_nonuniform_scaling(&Vector3::repeat(contrast)))
+  }
+
+  pub fn swap_red_green() -> Self {
+    Self::mixer([[0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]])
+  }
+
+  pub fn swap_red_blue() -> Self {
+    Self::mixer([[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]])
+  }
+
+  pub fn swap_green_blue() -> Self {
+    Self::mixer([[1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]])
+  }
+
+  // Mix channels, with each row of `mixer` giving the weights of the red,
+  // green, and blue input channels in the red, green, and blue output
+  // channels. Weights apply to colors in [0, 1], so are converted to act on
+  // color vectors in [-1, 1].
+  pub fn mixer(mixer: [[f32; 3]; 3]) -> Self {
+    let mut matrix = Matrix4::identity();
+    for (row, weights) in mixer.iter().enumerate() {
+      for (column, weight) in weights.iter().enumerate() {
+        matrix[(row, column)] = *weight;
+      }
+      matrix[(row, 3)] = weights.iter().sum::<f32>() - 1.0;
+    }
+    Self(matrix)
+  }
+}
+
+impl Default for ColorTransform {
+  fn default() -> Self {
+    Self::invert()
+  }
+}
+
+impl Mul for ColorTransform {
+  type Output = Self;
+
+  fn mul(self, rhs: Self) -> Self {
+    Self(self.0 * rhs.0)
+  }
+}
+
+impl From<ColorTransform> for Matrix4 {
+  fn from(color_transform: ColorTransform) -> Self {
+    color_transform.0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, approx::assert_abs_diff_eq};
+
+  // Apply `transform` to `color` the same way the fragment shader does
+  fn apply(transform: ColorTransform, color: [f32; 3]) -> [f32; 3] {
+    let vector = nalgebra::Vector4::new(
+      color[0] * 2.0 - 1.0,
+      color[1] * 2.0 - 1.0,
+      color[2] * 2.0 - 1.0,
+      1.0,
+    );
+    let transformed = transform.0 * vector;
+    [
+      (transformed.x + 1.0) / 2.0,
+      (transformed.y + 1.0) / 2.0,
+      (transformed.z + 1.0) / 2.0,
+    ]
+  }
+
+  #[test]
+  fn mixer_identity() {
+    let mixer = ColorTransform::mixer([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+    assert_abs_diff_eq!(mixer.0, Matrix4::identity());
+  }
+
+  #[test]
+  fn mixer_weights_apply_to_colors() {
+    let mixer = ColorTransform::mixer([[0.0, 1.0, 0.0], [0.5, 0.5, 0.0], [0.0, 0.0, 0.0]]);
+
+    for color in [[0.0, 0.0, 0.0], [1.0, 0.5, 0.25], [0.2, 0.4, 0.8]] {
+      let [r, g, b] = apply(mixer, color);
+      assert_abs_diff_eq!(r, color[1], epsilon = 1e-6);
+      assert_abs_diff_eq!(g, (color[0] + color[1]) / 2.0, epsilon = 1e-6);
+      assert_abs_diff_eq!(b, 0.0, epsilon = 1e-6);
+    }
+  }
+
+  #[test]
+  fn saturation_zero_is_grayscale() {
+    let [r, g, b] = apply(ColorTransform::saturation(0.0), [1.0, 0.0, 0.0]);
+    assert_abs_diff_eq!(r, 0.2126, epsilon = 1e-6);
+    assert_abs_diff_eq!(g, 0.2126, epsilon = 1e-6);
+    assert_abs_diff_eq!(b, 0.2126, epsilon = 1e-6);
+  }
+}
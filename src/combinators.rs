@@ -0,0 +1,508 @@
+use super::*;
+
+// Run `f`, scaling the alpha of all filters it renders by `alpha`.
+pub(crate) fn with_alpha(alpha: f32, f: impl FnOnce()) {
+  let previous = ALPHA.with(|current| current.replace(current.get() * alpha));
+
+  f();
+
+  ALPHA.with(|current| current.set(previous));
+}
+
+pub struct Sequence<A, B> {
+  pub(crate) first: A,
+  pub(crate) second: B,
+  pub(crate) duration: f32,
+  pub(crate) start: Option<f32>,
+  pub(crate) switched: bool,
+}
+
+impl<A: Process, B: Process> Process for Sequence<A, B> {
+  fn clear(&self) -> bool {
+    if self.switched {
+      self.second.clear()
+    } else {
+      self.first.clear()
+    }
+  }
+
+  fn frame(&mut self, frame: Frame) {
+    let start = *self.start.get_or_insert(frame.time);
+
+    if !self.switched && frame.time - start >= self.duration * 1000.0 {
+      self.first.on_stop();
+      self.second.init();
+      self.switched = true;
+    }
+
+    if self.switched {
+      self.second.frame(frame);
+    } else {
+      self.first.frame(frame);
+    }
+  }
+
+  fn init(&mut self) {
+    self.first.init();
+  }
+
+  fn on_beat(&mut self, beat: u64) {
+    if self.switched {
+      self.second.on_beat(beat);
+    } else {
+      self.first.on_beat(beat);
+    }
+  }
+
+  fn on_key(&mut self, key: &str) {
+    if self.switched {
+      self.second.on_key(key);
+    } else {
+      self.first.on_key(key);
+    }
+  }
+
+  fn on_resize(&mut self, width: u32, height: u32) {
+    if self.switched {
+      self.second.on_resize(width, height);
+    } else {
+      self.first.on_resize(width, height);
+    }
+  }
+
+  fn on_stop(&mut self) {
+    if self.switched {
+      self.second.on_stop();
+    } else {
+      self.first.on_stop();
+    }
+  }
+
+  fn on_widget(&mut self, key: &str, value: &serde_json::Value) {
+    if self.switched {
+      self.second.on_widget(key, value);
+    } else {
+      self.first.on_widget(key, value);
+    }
+  }
+}
+
+pub struct Cycle {
+  index: usize,
+  processes: Vec<Box<dyn Process>>,
+}
+
+// Run each of `processes` in turn, advancing to the next process on every
+// beat, and wrapping around to the first process after the last.
+//
+// ```
+// cycle(vec![Box::new(x), Box::new(kaleidoscope), Box::new(orbs)]).execute();
+// ```
+pub fn cycle(processes: Vec<Box<dyn Process>>) -> Cycle {
+  Cycle {
+    index: 0,
+    processes,
+  }
+}
+
+impl Cycle {
+  fn current(&mut self) -> Option<&mut Box<dyn Process>> {
+    self.processes.get_mut(self.index)
+  }
+}
+
+impl Process for Cycle {
+  fn clear(&self) -> bool {
+    self
+      .processes
+      .get(self.index)
+      .map(|process| process.clear())
+      .unwrap_or(true)
+  }
+
+  fn frame(&mut self, frame: Frame) {
+    if let Some(process) = self.current() {
+      process.frame(frame);
+    }
+  }
+
+  fn init(&mut self) {
+    if let Some(process) = self.current() {
+      process.init();
+    }
+  }
+
+  fn on_beat(&mut self, beat: u64) {
+    if self.processes.is_empty() {
+      return;
+    }
+
+    self.processes[self.index].on_stop();
+    self.index = (self.index + 1) % self.processes.len();
+    self.processes[self.index].init();
+    self.processes[self.index].on_beat(beat);
+  }
+
+  fn on_key(&mut self, key: &str) {
+    if let Some(process) = self.current() {
+      process.on_key(key);
+    }
+  }
+
+  fn on_resize(&mut self, width: u32, height: u32) {
+    if let Some(process) = self.current() {
+      process.on_resize(width, height);
+    }
+  }
+
+  fn on_stop(&mut self) {
+    if let Some(process) = self.current() {
+      process.on_stop();
+    }
+  }
+
+  fn on_widget(&mut self, key: &str, value: &serde_json::Value) {
+    if let Some(process) = self.current() {
+      process.on_widget(key, value);
+    }
+  }
+}
+
+pub struct Layer<A, B> {
+  pub(crate) bottom: A,
+  pub(crate) top: B,
+}
+
+impl<A: Process, B: Process> Process for Layer<A, B> {
+  fn clear(&self) -> bool {
+    self.bottom.clear()
+  }
+
+  fn frame(&mut self, frame: Frame) {
+    self.bottom.frame(frame);
+    self.top.frame(frame);
+  }
+
+  fn init(&mut self) {
+    self.bottom.init();
+    self.top.init();
+  }
+
+  fn on_beat(&mut self, beat: u64) {
+    self.bottom.on_beat(beat);
+    self.top.on_beat(beat);
+  }
+
+  fn on_key(&mut self, key: &str) {
+    self.bottom.on_key(key);
+    self.top.on_key(key);
+  }
+
+  fn on_resize(&mut self, width: u32, height: u32) {
+    self.bottom.on_resize(width, height);
+    self.top.on_resize(width, height);
+  }
+
+  fn on_stop(&mut self) {
+    self.bottom.on_stop();
+    self.top.on_stop();
+  }
+
+  fn on_widget(&mut self, key: &str, value: &serde_json::Value) {
+    self.bottom.on_widget(key, value);
+    self.top.on_widget(key, value);
+  }
+}
+
+// Fade from one process to another over `duration` seconds, starting from
+// the first frame. The second process is rendered on top of the first, with
+// the alpha of its filters scaled by the progress of the fade.
+pub struct Crossfade<A, B> {
+  pub(crate) from: A,
+  pub(crate) to: B,
+  pub(crate) duration: f32,
+  pub(crate) progress: f32,
+  pub(crate) start: Option<f32>,
+}
+
+impl<A: Process, B: Process> Process for Crossfade<A, B> {
+  fn clear(&self) -> bool {
+    if self.progress < 1.0 {
+      self.from.clear()
+    } else {
+      self.to.clear()
+    }
+  }
+
+  fn frame(&mut self, frame: Frame) {
+    let start = *self.start.get_or_insert(frame.time);
+
+    let progress = if self.duration > 0.0 {
+      ((frame.time - start) / (self.duration * 1000.0)).clamp(0.0, 1.0)
+    } else {
+      1.0
+    };
+
+    if progress == 1.0 && self.progress < 1.0 {
+      self.from.on_stop();
+    }
+
+    self.progress = progress;
+
+    if progress < 1.0 {
+      self.from.frame(frame);
+    }
+
+    if progress > 0.0 {
+      let to = &mut self.to;
+      with_alpha(progress, || to.frame(frame));
+    }
+  }
+
+  fn init(&mut self) {
+    self.from.init();
+    self.to.init();
+  }
+
+  fn on_beat(&mut self, beat: u64) {
+    if self.progress < 1.0 {
+      self.from.on_beat(beat);
+    }
+    self.to.on_beat(beat);
+  }
+
+  fn on_key(&mut self, key: &str) {
+    if self.progress < 1.0 {
+      self.from.on_key(key);
+    }
+    self.to.on_key(key);
+  }
+
+  fn on_resize(&mut self, width: u32, height: u32) {
+    if self.progress < 1.0 {
+      self.from.on_resize(width, height);
+    }
+    self.to.on_resize(width, height);
+  }
+
+  fn on_stop(&mut self) {
+    if self.progress < 1.0 {
+      self.from.on_stop();
+    }
+    self.to.on_stop();
+  }
+
+  fn on_widget(&mut self, key: &str, value: &serde_json::Value) {
+    if self.progress < 1.0 {
+      self.from.on_widget(key, value);
+    }
+    self.to.on_widget(key, value);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, std::fmt::Display};
+
+  type Log = Rc<RefCell<Vec<String>>>;
+
+  struct Recorder {
+    clear: bool,
+    log: Log,
+    name: &'static str,
+  }
+
+  fn recorder(name: &'static str, log: &Log) -> Recorder {
+    Recorder {
+      clear: true,
+      log: log.clone(),
+      name,
+    }
+  }
+
+  impl Recorder {
+    fn record(&self, event: impl Display) {
+      self.log.borrow_mut().push(format!("{} {event}", self.name));
+    }
+  }
+
+  impl Process for Recorder {
+    fn clear(&self) -> bool {
+      self.clear
+    }
+
+    fn frame(&mut self, _frame: Frame) {
+      self.record(format!("frame {}", ALPHA.with(Cell::get)));
+    }
+
+    fn init(&mut self) {
+      self.record("init");
+    }
+
+    fn on_beat(&mut self, beat: u64) {
+      self.record(format!("beat {beat}"));
+    }
+
+    fn on_key(&mut self, key: &str) {
+      self.record(format!("key {key}"));
+    }
+
+    fn on_stop(&mut self) {
+      self.record("stop");
+    }
+  }
+
+  fn at(time: f32) -> Frame {
+    Frame {
+      time,
+      ..Frame::default()
+    }
+  }
+
+  #[test]
+  fn sequence_switches_after_duration() {
+    let log = Log::default();
+
+    let mut sequence = recorder("a", &log).then(1.0, recorder("b", &log));
+
+    sequence.init();
+    for time in [0.0, 500.0, 1000.0, 1500.0] {
+      sequence.frame(at(time));
+    }
+    sequence.on_key("k");
+    sequence.on_stop();
+
+    assert_eq!(
+      *log.borrow(),
+      [
+        "a init",
+        "a frame 1",
+        "a frame 1",
+        "a stop",
+        "b init",
+        "b frame 1",
+        "b frame 1",
+        "b key k",
+        "b stop",
+      ],
+    );
+  }
+
+  #[test]
+  fn sequence_duration_starts_at_first_frame() {
+    let log = Log::default();
+
+    let mut sequence = recorder("a", &log).then(1.0, recorder("b", &log));
+
+    sequence.init();
+    sequence.frame(at(5000.0));
+    sequence.frame(at(5500.0));
+
+    assert_eq!(*log.borrow(), ["a init", "a frame 1", "a frame 1"]);
+  }
+
+  #[test]
+  fn cycle_advances_on_beat_and_wraps() {
+    let log = Log::default();
+
+    let mut cycle = cycle(vec![
+      Box::new(recorder("a", &log)),
+      Box::new(recorder("b", &log)),
+    ]);
+
+    cycle.init();
+    cycle.frame(at(0.0));
+    cycle.on_beat(1);
+    cycle.frame(at(0.0));
+    cycle.on_beat(2);
+    cycle.frame(at(0.0));
+    cycle.on_stop();
+
+    assert_eq!(
+      *log.borrow(),
+      [
+        "a init",
+        "a frame 1",
+        "a stop",
+        "b init",
+        "b beat 1",
+        "b frame 1",
+        "b stop",
+        "a init",
+        "a beat 2",
+        "a frame 1",
+        "a stop",
+      ],
+    );
+  }
+
+  #[test]
+  fn empty_cycle() {
+    let mut cycle = cycle(Vec::new());
+    cycle.init();
+    cycle.frame(at(0.0));
+    cycle.on_beat(1);
+    cycle.on_stop();
+    assert!(cycle.clear());
+  }
+
+  #[test]
+  fn crossfade_ramps_alpha_and_hands_off() {
+    let log = Log::default();
+
+    let mut crossfade = recorder("a", &log).crossfade(
+      1.0,
+      Recorder {
+        clear: false,
+        ..recorder("b", &log)
+      },
+    );
+
+    crossfade.init();
+    assert!(crossfade.clear());
+
+    crossfade.frame(at(0.0));
+    crossfade.frame(at(250.0));
+    crossfade.frame(at(500.0));
+    assert!(crossfade.clear());
+
+    crossfade.frame(at(1000.0));
+    assert!(!crossfade.clear());
+
+    crossfade.frame(at(1500.0));
+    crossfade.on_key("k");
+    crossfade.on_stop();
+
+    assert_eq!(
+      *log.borrow(),
+      [
+        "a init",
+        "b init",
+        "a frame 1",
+        "a frame 1",
+        "b frame 0.25",
+        "a frame 1",
+        "b frame 0.5",
+        "a stop",
+        "b frame 1",
+        "b frame 1",
+        "b key k",
+        "b stop",
+      ],
+    );
+
+    assert_eq!(ALPHA.with(Cell::get), 1.0);
+  }
+
+  #[test]
+  fn crossfade_alpha_composes() {
+    let log = Log::default();
+
+    let mut crossfade = recorder("a", &log).crossfade(1.0, recorder("b", &log));
+
+    crossfade.frame(at(0.0));
+    with_alpha(0.5, || crossfade.frame(at(500.0)));
+
+    assert_eq!(*log.borrow(), ["a frame 1", "a frame 0.5", "b frame 0.25"],);
+  }
+}
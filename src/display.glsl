@@ -0,0 +1,76 @@
+#version 300 es
+
+precision highp float;
+
+uniform float bloom_intensity;
+uniform float bloom_radius;
+uniform float bloom_threshold;
+uniform float chromatic_aberration;
+uniform float gamma;
+uniform float scanline_intensity;
+uniform float scanline_spacing;
+uniform float vignette;
+uniform sampler2D source;
+uniform vec2 extent;
+uniform vec2 origin;
+uniform vec2 size;
+
+out vec4 output_color;
+
+vec3 source_sample(vec2 pixel) {
+  return texture(source, clamp((pixel - origin) / extent, 0.0, 1.0)).rgb;
+}
+
+vec3 bloom(vec2 pixel) {
+  vec3 sum = vec3(0.0);
+  float weight = 0.0;
+  for (int x = -2; x <= 2; x++) {
+    for (int y = -2; y <= 2; y++) {
+      vec2 step = vec2(x, y);
+      float w = exp(-dot(step, step) / 4.0);
+      vec3 color = source_sample(pixel + step * bloom_radius / 2.0);
+      sum += max(color - bloom_threshold, 0.0) * w;
+      weight += w;
+    }
+  }
+  return sum / weight * bloom_intensity;
+}
+
+void main() {
+  // Get fragment coordinates in pixels and transform to [-1, 1]
+  vec2 pixel = gl_FragCoord.xy;
+  vec2 position = pixel / size * 2.0 - 1.0;
+
+  // Output black outside of the scaled source
+  vec2 coordinates = (pixel - origin) / extent;
+  if (any(lessThan(coordinates, vec2(0.0))) || any(greaterThan(coordinates, vec2(1.0)))) {
+    output_color = vec4(0.0, 0.0, 0.0, 1.0);
+    return;
+  }
+
+  // Sample red and blue channels offset away from the center
+  vec2 aberration = position * chromatic_aberration;
+  vec3 color = vec3(
+    source_sample(pixel + aberration).r,
+    source_sample(pixel).g,
+    source_sample(pixel - aberration).b
+  );
+
+  // Add blurred bright areas
+  if (bloom_intensity > 0.0) {
+    color += bloom(pixel);
+  }
+
+  // Darken edges
+  color *= clamp(1.0 - vignette * dot(position, position) / 2.0, 0.0, 1.0);
+
+  // Darken every other run of `scanline_spacing` rows
+  if (scanline_spacing > 0.0 && mod(pixel.y, scanline_spacing * 2.0) >= scanline_spacing) {
+    color *= 1.0 - scanline_intensity;
+  }
+
+  // Apply gamma correction
+  color = pow(clamp(color, 0.0, 1.0), vec3(1.0 / gamma));
+
+  output_color = vec4(color, 1.0);
+}
@@ -2,6 +2,31 @@
 
 precision highp float;
 
+const int BLEND_MODE_ADD = 0;
+const int BLEND_MODE_DIFFERENCE = 1;
+const int BLEND_MODE_MAX = 2;
+const int BLEND_MODE_MIN = 3;
+const int BLEND_MODE_MULTIPLY = 4;
+const int BLEND_MODE_NORMAL = 5;
+const int BLEND_MODE_OVERLAY = 6;
+const int BLEND_MODE_SCREEN = 7;
+const int BLEND_MODE_XOR = 8;
+
+const int CHANNEL_BLUE = 0;
+const int CHANNEL_GREEN = 1;
+const int CHANNEL_LUMINANCE = 2;
+const int CHANNEL_RED = 3;
+
+const int COLOR_OP_CONTRAST = 0;
+const int COLOR_OP_HUE_ROTATE = 1;
+const int COLOR_OP_INVERT_LUMINANCE = 2;
+const int COLOR_OP_SATURATION = 3;
+const int COLOR_OP_VALUE = 4;
+
+const int DITHER_BAYER = 1;
+const int DITHER_BLUE_NOISE = 2;
+const int DITHER_NONE = 0;
+
 const int FIELD_ALL = 0;
 const int FIELD_CHECK = 1;
 const int FIELD_CIRCLE = 2;
@@ -16,22 +41,62 @@ const int FIELD_TOP = 10;
 const int FIELD_WAVE = 11;
 const int FIELD_X = 12;
 
+const vec3 LUMINANCE = vec3(0.2126, 0.7152, 0.0722);
+
+const int MAX_COLOR_OPS = 8;
+
+const float TAU = 6.283185307179586;
+
+uniform bool automaton;
 uniform bool coordinates;
+uniform bool curves;
+uniform bool delay;
+uniform bool gradient_map;
+uniform bool linear;
+uniform bool posterize;
+uniform bool reaction_diffusion;
+uniform bool sort;
+uniform bool sort_ascending;
+uniform bool threshold;
 uniform bool wrap;
 uniform float alpha;
+uniform float automaton_threshold;
+uniform float color_op_parameters[MAX_COLOR_OPS];
+uniform float kernel[25];
 uniform float resolution;
+uniform float sort_threshold;
 uniform float spl;
+uniform float threshold_value;
+uniform float tile_resolution;
+uniform int automaton_channel;
+uniform int blend_mode;
+uniform int color_op_count;
+uniform int color_ops[MAX_COLOR_OPS];
+uniform int dither;
 uniform int field;
+uniform int gradient_map_channel;
+uniform int kernel_size;
+uniform int sort_axis;
 uniform mat3 position_transform;
 uniform mat4 color_transform;
 uniform sampler2D audio_frequency;
 uniform sampler2D audio_time_domain;
+uniform sampler2D blue_noise;
+uniform sampler2D chemicals;
+uniform sampler2D curves_table;
+uniform sampler2D delayed;
+uniform sampler2D palette;
 uniform sampler2D source;
+uniform uint automaton_birth;
+uniform uint automaton_survival;
 uniform uint mod_divisor;
 uniform uint mod_remainder;
 uniform uint rows_off;
 uniform uint rows_on;
+uniform uint sort_parity;
+uniform vec2 tile_offset;
 uniform vec3 default_color;
+uniform vec3 posterize_levels;
 
 out vec4 output_color;
 
@@ -43,6 +108,274 @@ vec3 octant(vec3 position) {
   return (position + 1.0) / 2.0;
 }
 
+// Convert sRGB encoded color to linear light. The input to `pow` is clamped
+// to the range where it is used, since `pow` is undefined for negative bases,
+// and `mix` propagates NaN even when it is weighted by zero.
+vec3 srgb_decode(vec3 color) {
+  return mix(
+    color / 12.92,
+    pow((max(color, 0.04045) + 0.055) / 1.055, vec3(2.4)),
+    step(0.04045, color)
+  );
+}
+
+// Convert linear light color to sRGB encoding
+vec3 srgb_encode(vec3 color) {
+  color = clamp(color, 0.0, 1.0);
+  return mix(
+    color * 12.92,
+    1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055,
+    step(0.0031308, color)
+  );
+}
+
+// Convert coordinates in [0, 1] over the whole image to coordinates in [0, 1]
+// over the current tile. When not rendering tiles, the tile covers the whole
+// image, `tile_offset` is zero, and the coordinates are returned unchanged.
+vec2 tile(vec2 coordinates) {
+  return (coordinates - tile_offset / resolution) * (resolution / tile_resolution);
+}
+
+vec3 chemicals_sample(vec2 coordinates) {
+  vec2 concentration = texture(chemicals, coordinates).rg;
+  return vec3(clamp(concentration.r - concentration.g, 0.0, 1.0));
+}
+
+// Sample the source image. Coordinates are clamped to the centers of the
+// image's edge pixels, so that kernels and automata sample the edge pixels
+// beyond the image's border, whether or not the image is being rendered in
+// tiles.
+vec3 source_sample(vec2 coordinates) {
+  return reaction_diffusion ? chemicals_sample(coordinates)
+    : delay ? texture(delayed, coordinates).rgb
+    : texture(source, tile(clamp(coordinates, 0.5 / resolution, 1.0 - 0.5 / resolution))).rgb;
+}
+
+// Sample the source using the convolution kernel, if any. Kernel weights are
+// stored in rows, from top to bottom.
+vec3 kernel_sample(vec2 coordinates) {
+  if (kernel_size == 0) {
+    return source_sample(coordinates);
+  }
+
+  int half_size = kernel_size / 2;
+  vec3 sum = vec3(0.0);
+  for (int row = 0; row < kernel_size; row++) {
+    for (int column = 0; column < kernel_size; column++) {
+      vec2 offset = vec2(column - half_size, half_size - row) / resolution;
+      sum += source_sample(coordinates + offset) * kernel[row * kernel_size + column];
+    }
+  }
+  return sum;
+}
+
+float channel(vec3 color, int channel) {
+  switch (channel) {
+    case CHANNEL_BLUE:
+      return color.b;
+    case CHANNEL_GREEN:
+      return color.g;
+    case CHANNEL_RED:
+      return color.r;
+    default:
+      return dot(color, LUMINANCE);
+  }
+}
+
+bool alive(vec2 coordinates) {
+  return channel(source_sample(coordinates), automaton_channel) > automaton_threshold;
+}
+
+// Advance the cellular automaton by one generation, returning white for live
+// cells and black for dead cells.
+vec3 automaton_sample(vec2 coordinates) {
+  uint neighbors = 0u;
+  for (int x = -1; x <= 1; x++) {
+    for (int y = -1; y <= 1; y++) {
+      if ((x != 0 || y != 0) && alive(coordinates + vec2(x, y) / resolution)) {
+        neighbors++;
+      }
+    }
+  }
+  uint rule = alive(coordinates) ? automaton_survival : automaton_birth;
+  return (rule >> neighbors & 1u) == 1u ? vec3(1.0) : vec3(0.0);
+}
+
+// Perform one pass of odd-even transposition sort along `sort_axis`. Each
+// pixel is paired with a neighbor, alternating between the neighbor before
+// and after it with `sort_parity`, and the pair is swapped if out of order.
+// Only pixels whose luminance is above `sort_threshold` are sorted, so
+// spans of bright pixels are sorted independently.
+vec3 sort_sample(vec2 coordinates) {
+  vec3 color = source_sample(coordinates);
+
+  uint index = uint(coordinates[sort_axis] * resolution);
+  bool first = (index + sort_parity) % 2u == 0u;
+
+  vec2 offset = vec2(0.0);
+  offset[sort_axis] = (first ? 1.0 : -1.0) / resolution;
+  vec2 neighbor_coordinates = coordinates + offset;
+
+  if (neighbor_coordinates[sort_axis] < 0.0 || neighbor_coordinates[sort_axis] > 1.0) {
+    return color;
+  }
+
+  vec3 neighbor = source_sample(neighbor_coordinates);
+
+  float luminance = dot(color, LUMINANCE);
+  float neighbor_luminance = dot(neighbor, LUMINANCE);
+
+  if (luminance <= sort_threshold || neighbor_luminance <= sort_threshold) {
+    return color;
+  }
+
+  bool swap = first == sort_ascending
+    ? luminance > neighbor_luminance
+    : luminance < neighbor_luminance;
+
+  return swap ? neighbor : color;
+}
+
+vec3 rgb_to_hsv(vec3 c) {
+  vec4 k = vec4(0.0, -1.0 / 3.0, 2.0 / 3.0, -1.0);
+  vec4 p = mix(vec4(c.bg, k.wz), vec4(c.gb, k.xy), step(c.b, c.g));
+  vec4 q = mix(vec4(p.xyw, c.r), vec4(c.r, p.yzx), step(p.x, c.r));
+  float d = q.x - min(q.w, q.y);
+  float e = 1.0e-10;
+  return vec3(abs(q.z + (q.w - q.y) / (6.0 * d + e)), d / (q.x + e), q.x);
+}
+
+vec3 hsv_to_rgb(vec3 c) {
+  vec3 p = abs(fract(vec3(c.x) + vec3(1.0, 2.0 / 3.0, 1.0 / 3.0)) * 6.0 - 3.0);
+  return c.z * mix(vec3(1.0), clamp(p - 1.0, 0.0, 1.0), c.y);
+}
+
+vec3 color_op(vec3 color, int op, float parameter) {
+  switch (op) {
+    case COLOR_OP_CONTRAST:
+      return (color - 0.5) * parameter + 0.5;
+    case COLOR_OP_HUE_ROTATE: {
+      vec3 hsv = rgb_to_hsv(clamp(color, 0.0, 1.0));
+      hsv.x = fract(hsv.x + parameter / TAU);
+      return hsv_to_rgb(hsv);
+    }
+    case COLOR_OP_INVERT_LUMINANCE:
+      return color + (1.0 - 2.0 * dot(color, LUMINANCE));
+    case COLOR_OP_SATURATION: {
+      vec3 hsv = rgb_to_hsv(clamp(color, 0.0, 1.0));
+      hsv.y = clamp(hsv.y * parameter, 0.0, 1.0);
+      return hsv_to_rgb(hsv);
+    }
+    case COLOR_OP_VALUE: {
+      vec3 hsv = rgb_to_hsv(clamp(color, 0.0, 1.0));
+      hsv.z = clamp(hsv.z * parameter, 0.0, 1.0);
+      return hsv_to_rgb(hsv);
+    }
+    default:
+      return color;
+  }
+}
+
+// Apply color operations in order
+vec3 color_ops_apply(vec3 color) {
+  for (int i = 0; i < color_op_count; i++) {
+    color = color_op(color, color_ops[i], color_op_parameters[i]);
+  }
+  return color;
+}
+
+// Map `channel` of `color` through the palette
+vec3 gradient_map_sample(vec3 color) {
+  float position = clamp(channel(color, gradient_map_channel), 0.0, 1.0);
+  return texture(palette, vec2((position * 255.0 + 0.5) / 256.0, 0.5)).rgb;
+}
+
+float curves_sample(float x, int channel) {
+  return texture(curves_table, vec2((clamp(x, 0.0, 1.0) * 255.0 + 0.5) / 256.0, 0.5))[channel];
+}
+
+// Apply red, green, and blue curves, stored in the red, green, and blue
+// channels of the curves table, and then the luminance curve, stored in the
+// alpha channel, by scaling the color to the new luminance
+vec3 curves_apply(vec3 color) {
+  color = vec3(curves_sample(color.r, 0), curves_sample(color.g, 1), curves_sample(color.b, 2));
+  float luminance = dot(color, LUMINANCE);
+  float mapped = curves_sample(luminance, 3);
+  return luminance > 0.0 ? color * (mapped / luminance) : vec3(mapped);
+}
+
+// Get the ordered dithering threshold in [0, 1) for a pixel
+float dither_threshold(uvec2 pixel) {
+  switch (dither) {
+    case DITHER_BAYER: {
+      // Build an 8×8 Bayer matrix value by interleaving the bits of the
+      // pixel's x and x xor y coordinates in reverse order
+      uint x = pixel.x;
+      uint y = pixel.x ^ pixel.y;
+      uint value = 0u;
+      for (int bit = 0; bit < 3; bit++) {
+        value = value << 2 | (y & 1u) << 1 | x & 1u;
+        x >>= 1;
+        y >>= 1;
+      }
+      return (float(value) + 0.5) / 64.0;
+    }
+    case DITHER_BLUE_NOISE: {
+      ivec2 size = textureSize(blue_noise, 0);
+      return texelFetch(blue_noise, ivec2(pixel) % size, 0).r + 0.5 / 256.0;
+    }
+    default:
+      return 0.5;
+  }
+}
+
+// Quantize color by posterizing and thresholding, using ordered dithering if
+// enabled
+vec3 quantize(vec3 color, uvec2 pixel) {
+  float offset = dither_threshold(pixel) - 0.5;
+
+  if (posterize) {
+    vec3 steps = max(posterize_levels - 1.0, 1.0);
+    color = clamp(floor(color * steps + 0.5 + offset) / steps, 0.0, 1.0);
+  }
+
+  if (threshold) {
+    color = step(threshold_value + offset, color);
+  }
+
+  return color;
+}
+
+// Blend `color` over `original`, before alpha blending
+vec3 blend(vec3 color, vec3 original) {
+  switch (blend_mode) {
+    case BLEND_MODE_ADD:
+      return min(color + original, 1.0);
+    case BLEND_MODE_DIFFERENCE:
+      return abs(color - original);
+    case BLEND_MODE_MAX:
+      return max(color, original);
+    case BLEND_MODE_MIN:
+      return min(color, original);
+    case BLEND_MODE_MULTIPLY:
+      return color * original;
+    case BLEND_MODE_OVERLAY:
+      return mix(
+        2.0 * color * original,
+        1.0 - 2.0 * (1.0 - color) * (1.0 - original),
+        step(0.5, original)
+      );
+    case BLEND_MODE_SCREEN:
+      return 1.0 - (1.0 - color) * (1.0 - original);
+    case BLEND_MODE_XOR:
+      return vec3(
+        uvec3(clamp(color, 0.0, 1.0) * 255.0 + 0.5) ^ uvec3(clamp(original, 0.0, 1.0) * 255.0 + 0.5)
+      ) / 255.0;
+    default:
+      return color;
+  }
+}
+
 float audio_frequency_sample(vec2 position) {
   return texture(audio_frequency, quadrant(position)).r;
 }
@@ -165,7 +498,7 @@ float distance_field(vec2 p, uvec2 px) {
 
 void main() {
   // Get fragment coordinates and transform to [-1, 1]
-  vec2 position = gl_FragCoord.xy / resolution * 2.0 - 1.0;
+  vec2 position = (gl_FragCoord.xy + tile_offset) / resolution * 2.0 - 1.0;
 
   // Transform position by position transform matrix
   vec2 transformed = (position_transform * vec3(position, 1.0)).xy;
@@ -177,11 +510,19 @@ void main() {
 
   // Sample color if in-bounds, otherwise use default color
   vec3 input_color = coordinates ? vec3(quadrant(wrapped), 0.0)
-    : abs(wrapped.x) <= 1.0 && abs(wrapped.y) <= 1.0 ? texture(source, quadrant(wrapped)).rgb
-    : default_color;
+    : abs(wrapped.x) > 1.0 || abs(wrapped.y) > 1.0 ? default_color
+    : automaton ? automaton_sample(quadrant(wrapped))
+    : sort ? sort_sample(quadrant(wrapped))
+    : kernel_sample(quadrant(wrapped));
 
   // Sample original color
-  vec3 original_color = texture(source, gl_FragCoord.xy / resolution).rgb;
+  vec3 original_color = texture(source, gl_FragCoord.xy / tile_resolution).rgb;
+
+  // Decode colors to linear light if enabled
+  if (linear) {
+    input_color = srgb_decode(input_color);
+    original_color = srgb_decode(original_color);
+  }
 
   // Calculate position in pixel coordinates, [0, resolution)
   uvec2 pixel_position = uvec2(quadrant(wrapped) * resolution);
@@ -193,7 +534,22 @@ void main() {
   vec4 transformed_color_vector = color_transform * vec4(color_vector, 1.0);
 
   // Convert color back from [-1,-1] to [0,1]
-  vec3 transformed_color = octant(transformed_color_vector.xyz);
+  vec3 transformed_color = color_ops_apply(octant(transformed_color_vector.xyz));
+
+  // Map color through palette if enabled
+  if (gradient_map) {
+    transformed_color = gradient_map_sample(transformed_color);
+  }
+
+  // Apply curves if enabled
+  if (curves) {
+    transformed_color = curves_apply(transformed_color);
+  }
+
+  // Quantize color
+  if (posterize || threshold) {
+    transformed_color = quantize(transformed_color, uvec2(gl_FragCoord.xy + tile_offset));
+  }
 
   // Get the signed distance from the field
   float distance = distance_field(wrapped, pixel_position);
@@ -202,7 +558,13 @@ void main() {
   float alpha = distance <= 0.0 ? alpha : 0.0;
 
   // Perform alpha blending
-  vec3 output_color_rgb = transformed_color * alpha + original_color * (1.0 - alpha);
+  vec3 blended_color = blend(transformed_color, original_color);
+  vec3 output_color_rgb = blended_color * alpha + original_color * (1.0 - alpha);
+
+  // Encode color back to sRGB if enabled
+  if (linear) {
+    output_color_rgb = srgb_encode(output_color_rgb);
+  }
 
   // Extend output color with opaque alpha channel
   output_color = vec4(output_color_rgb, 1.0);
@@ -8,22 +8,52 @@ pub(crate) struct Gpu {
   audio_time_domain_array: Float32Array,
   audio_time_domain_data: Vec<f32>,
   audio_time_domain_texture: WebGlTexture,
+  blue_noise_texture: WebGlTexture,
   canvas: HtmlCanvasElement,
+  chemicals: Option<(WebGlTexture, WebGlTexture)>,
+  curves: Option<Curves>,
+  curves_texture: WebGlTexture,
   decibels_max: f32,
   decibels_min: f32,
+  delay: usize,
   destination: WebGlTexture,
+  display_program: WebGlProgram,
+  display_uniforms: Uniforms,
+  effects: Vec<Effect>,
+  float_color_buffer: bool,
   frame_buffer: WebGlFramebuffer,
   gl: WebGl2RenderingContext,
   height: u32,
+  history: Option<Vec<Filter>>,
   lock_resolution: bool,
+  palette: Palette,
+  palette_texture: WebGlTexture,
+  precision: Precision,
+  program: WebGlProgram,
+  reaction_diffusion_program: WebGlProgram,
+  reaction_diffusion_uniforms: Uniforms,
+  rendered: bool,
   resolution: u32,
+  rewind: VecDeque<WebGlTexture>,
+  rewind_capacity: usize,
+  rewind_position: usize,
+  scaling: Scaling,
+  snapshot: Option<ImageBuffer<image::Rgba<u8>, Vec<u8>>>,
+  sort_parity: u32,
   source: WebGlTexture,
-  uniforms: BTreeMap<String, WebGlUniformLocation>,
+  uniforms: Uniforms,
   width: u32,
   window: Window,
 }
 
 impl Gpu {
+  const BLUE_NOISE_SIZE: usize = 32;
+  const LOOKUP_TABLE_SIZE: usize = 256;
+  const MAX_COLOR_OPS: usize = 8;
+  const MAX_HISTORY: usize = 1 << 16;
+  const MAX_TILED_RESOLUTION: u32 = 16384;
+  const TILE_RESOLUTION: u32 = 2048;
+
   pub(super) fn new(
     window: &Window,
     canvas: &HtmlCanvasElement,
@@ -45,58 +75,39 @@ impl Gpu {
       .ok_or("Failed to retrieve webgl2 context")?
       .cast::<WebGl2RenderingContext>()?;
 
-    gl.enable(WebGl2RenderingContext::CULL_FACE);
+    Self::with_context(gl, window, canvas, analyser_node)
+  }
 
-    let program = {
-      let program = gl.create_program().ok_or("Failed to create program")?;
+  fn with_context(
+    gl: WebGl2RenderingContext,
+    window: &Window,
+    canvas: &HtmlCanvasElement,
+    analyser_node: &AnalyserNode,
+  ) -> Result<Self> {
+    gl.enable(WebGl2RenderingContext::CULL_FACE);
 
-      let vertex = gl
-        .create_shader(WebGl2RenderingContext::VERTEX_SHADER)
-        .ok_or("Failed to create shader")?;
+    let program = Self::create_program(&gl, include_str!("fragment.glsl"))?;
 
-      gl.shader_source(&vertex, include_str!("vertex.glsl"));
-      gl.compile_shader(&vertex);
+    let display_program = Self::create_program(&gl, include_str!("display.glsl"))?;
 
-      if !gl.get_shader_parameter(&vertex, WebGl2RenderingContext::COMPILE_STATUS) {
-        return Err(
-          gl.get_shader_info_log(&vertex)
-            .ok_or("Failed to get shader info log")?
-            .into(),
-        );
-      }
+    let mut display_uniforms = Uniforms::new(&gl, &display_program)?;
 
-      let fragment = gl
-        .create_shader(WebGl2RenderingContext::FRAGMENT_SHADER)
-        .ok_or("Failed to create shader")?;
+    gl.use_program(Some(&display_program));
 
-      gl.shader_source(&fragment, include_str!("fragment.glsl"));
-      gl.compile_shader(&fragment);
+    display_uniforms.set(&gl, "source", Uniform::Int(0))?;
 
-      if !gl.get_shader_parameter(&fragment, WebGl2RenderingContext::COMPILE_STATUS) {
-        return Err(
-          gl.get_shader_info_log(&fragment)
-            .ok_or("Failed to get shader info log")?
-            .into(),
-        );
-      }
+    let reaction_diffusion_program =
+      Self::create_program(&gl, include_str!("reaction_diffusion.glsl"))?;
 
-      gl.attach_shader(&program, &vertex);
-      gl.attach_shader(&program, &fragment);
+    let mut reaction_diffusion_uniforms = Uniforms::new(&gl, &reaction_diffusion_program)?;
 
-      gl.link_program(&program);
+    gl.use_program(Some(&reaction_diffusion_program));
 
-      if !gl.get_program_parameter(&program, WebGl2RenderingContext::LINK_STATUS) {
-        return Err(
-          gl.get_program_info_log(&program)
-            .ok_or("Failed to get program log info")?
-            .into(),
-        );
-      }
+    reaction_diffusion_uniforms.set(&gl, "state", Uniform::Int(0))?;
 
-      gl.use_program(Some(&program));
+    gl.use_program(Some(&program));
 
-      program
-    };
+    let float_color_buffer = gl.get_extension("EXT_color_buffer_float")?.is_some();
 
     let width = canvas.width();
     let height = canvas.height();
@@ -106,23 +117,55 @@ impl Gpu {
       .create_framebuffer()
       .ok_or("Failed to create framebuffer")?;
 
-    let uniform_count = gl
-      .get_program_parameter(&program, WebGl2RenderingContext::ACTIVE_UNIFORMS)
-      .cast::<js_sys::Number>()?
-      .value_of() as u32;
+    let mut uniforms = Uniforms::new(&gl, &program)?;
+
+    uniforms.set(&gl, "source", Uniform::Int(0))?;
+    uniforms.set(&gl, "audio_time_domain", Uniform::Int(1))?;
+    uniforms.set(&gl, "audio_frequency", Uniform::Int(2))?;
+    uniforms.set(&gl, "delayed", Uniform::Int(3))?;
+    uniforms.set(&gl, "chemicals", Uniform::Int(4))?;
+    uniforms.set(&gl, "palette", Uniform::Int(5))?;
+    uniforms.set(&gl, "blue_noise", Uniform::Int(6))?;
+    uniforms.set(&gl, "curves_table", Uniform::Int(7))?;
+    uniforms.set(&gl, "resolution", Uniform::Float(resolution as f32))?;
+    uniforms.set(&gl, "tile_resolution", Uniform::Float(resolution as f32))?;
+    uniforms.set(&gl, "tile_offset", Uniform::Vec2([0.0, 0.0]))?;
 
-    let uniforms = (0..uniform_count)
-      .map(|i| {
-        let info = gl.get_active_uniform(&program, i).unwrap();
-        let name = info.name();
-        let location = gl.get_uniform_location(&program, &name).unwrap();
-        (name, location)
-      })
-      .collect::<BTreeMap<String, WebGlUniformLocation>>();
+    gl.viewport(0, 0, resolution as i32, resolution as i32);
 
-    gl.uniform1i(Some(uniforms.get("source").unwrap()), 0);
-    gl.uniform1i(Some(uniforms.get("audio_time_domain").unwrap()), 1);
-    gl.uniform1i(Some(uniforms.get("audio_frequency").unwrap()), 2);
+    let palette_texture = Self::create_lookup_table(&gl)?;
+
+    let curves_texture = Self::create_lookup_table(&gl)?;
+
+    let blue_noise_texture = gl
+      .create_texture()
+      .ok_or("Failed to create blue noise texture")?;
+
+    gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+    gl.bind_texture(
+      WebGl2RenderingContext::TEXTURE_2D,
+      Some(&blue_noise_texture),
+    );
+
+    gl.tex_storage_2d(
+      WebGl2RenderingContext::TEXTURE_2D,
+      1,
+      WebGl2RenderingContext::R8,
+      Self::BLUE_NOISE_SIZE as i32,
+      Self::BLUE_NOISE_SIZE as i32,
+    );
+
+    gl.tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+      WebGl2RenderingContext::TEXTURE_2D,
+      0,
+      0,
+      0,
+      Self::BLUE_NOISE_SIZE as i32,
+      Self::BLUE_NOISE_SIZE as i32,
+      WebGl2RenderingContext::RED,
+      WebGl2RenderingContext::UNSIGNED_BYTE,
+      Some(&blue_noise(Self::BLUE_NOISE_SIZE)),
+    )?;
 
     let audio_time_domain_texture = gl
       .create_texture()
@@ -188,31 +231,71 @@ impl Gpu {
       1,
     );
 
-    Ok(Self {
-      source: Self::create_texture(&gl, resolution)?,
-      destination: Self::create_texture(&gl, resolution)?,
+    let mut gpu = Self {
+      source: Self::create_texture(&gl, resolution, WebGl2RenderingContext::RGBA8)?,
+      destination: Self::create_texture(&gl, resolution, WebGl2RenderingContext::RGBA8)?,
       analyser_node: analyser_node.clone(),
       audio_time_domain_array: Float32Array::new_with_length(fft_size),
       audio_time_domain_data: vec![0.0; fft_size as usize],
       audio_time_domain_texture,
+      blue_noise_texture,
       audio_frequency_array: Float32Array::new_with_length(frequency_bin_count),
       audio_frequency_data: vec![0.0; frequency_bin_count as usize],
       audio_frequency_texture,
       canvas: canvas.clone(),
+      chemicals: None,
+      curves: None,
+      curves_texture,
       decibels_min: -100.0,
       decibels_max: -30.0,
+      delay: 0,
+      display_program,
+      display_uniforms,
+      effects: Vec::new(),
+      float_color_buffer,
       frame_buffer,
       gl,
       height,
+      history: Some(Vec::new()),
       lock_resolution: false,
+      palette: Palette::grayscale(),
+      palette_texture,
+      precision: Precision::Byte,
+      program,
+      reaction_diffusion_program,
+      reaction_diffusion_uniforms,
+      rendered: false,
       resolution,
+      rewind: VecDeque::new(),
+      rewind_capacity: 0,
+      rewind_position: 0,
+      scaling: Scaling::Fill,
+      snapshot: None,
+      sort_parity: 0,
       uniforms,
       width,
       window: window.clone(),
-    })
+    };
+
+    gpu.set_palette(Palette::grayscale())?;
+
+    Ok(gpu)
   }
 
-  pub(crate) fn present(&self) -> Result {
+  pub(crate) fn present(&mut self) -> Result {
+    if self.context_lost() {
+      return Ok(());
+    }
+
+    let (x, y, width, height) = self.destination_rectangle();
+
+    // Float textures cannot be blitted to the default framebuffer, which has
+    // a fixed-point format, so they are drawn with the display program, which
+    // is a passthrough when there are no effects
+    if !self.effects.is_empty() || self.precision != Precision::Byte {
+      return self.present_effects(x, y, width, height);
+    }
+
     self.gl.bind_framebuffer(
       WebGl2RenderingContext::READ_FRAMEBUFFER,
       Some(&self.frame_buffer),
@@ -226,26 +309,25 @@ impl Gpu {
       WebGl2RenderingContext::READ_FRAMEBUFFER,
       WebGl2RenderingContext::COLOR_ATTACHMENT0,
       WebGl2RenderingContext::TEXTURE_2D,
-      Some(&self.source),
+      Some(self.displayed()),
       0,
     );
 
-    let width = self.width as i32;
-    let height = self.height as i32;
-    let resolution = self.resolution as i32;
+    self
+      .gl
+      .clear_bufferfv_with_f32_array(WebGl2RenderingContext::COLOR, 0, &[0.0, 0.0, 0.0, 1.0]);
 
-    let dx = (resolution - width) / 2;
-    let dy = (resolution - height) / 2;
+    let resolution = self.resolution as i32;
 
     self.gl.blit_framebuffer(
-      dx,
-      dy,
-      width + dx,
-      height + dy,
       0,
       0,
-      width,
-      height,
+      resolution,
+      resolution,
+      x,
+      y,
+      x + width,
+      y + height,
       WebGl2RenderingContext::COLOR_BUFFER_BIT,
       WebGl2RenderingContext::NEAREST,
     );
@@ -253,9 +335,134 @@ impl Gpu {
     Ok(())
   }
 
-  pub(crate) fn render(&mut self, filter: &Filter) -> Result {
-    self.resize()?;
+  fn displayed(&self) -> &WebGlTexture {
+    match self.rewind_position {
+      0 => &self.source,
+      position => &self.rewind[position.min(self.rewind.len() - 1)],
+    }
+  }
+
+  fn destination_rectangle(&self) -> (i32, i32, i32, i32) {
+    let width = self.width as i32;
+    let height = self.height as i32;
+    let resolution = self.resolution as i32;
+
+    let (destination_width, destination_height) = match self.scaling {
+      Scaling::Fill => (width.max(height), width.max(height)),
+      Scaling::Fit => (width.min(height), width.min(height)),
+      Scaling::Integer => {
+        let size = resolution * (width.min(height) / resolution).max(1);
+        (size, size)
+      }
+      Scaling::Stretch => (width, height),
+    };
+
+    (
+      (width - destination_width) / 2,
+      (height - destination_height) / 2,
+      destination_width,
+      destination_height,
+    )
+  }
+
+  // Present with the display program, restoring the main program and
+  // viewport even if presenting fails, so that they match the state expected
+  // by `render`
+  fn present_effects(&mut self, x: i32, y: i32, width: i32, height: i32) -> Result {
+    self.gl.use_program(Some(&self.display_program));
+
+    let result = self.draw_effects(x, y, width, height);
+
+    self
+      .gl
+      .viewport(0, 0, self.resolution as i32, self.resolution as i32);
+
+    self.gl.use_program(Some(&self.program));
+
+    result
+  }
+
+  fn draw_effects(&mut self, x: i32, y: i32, width: i32, height: i32) -> Result {
+    let mut bloom_intensity = 0.0;
+    let mut bloom_radius = 0.0;
+    let mut bloom_threshold = 0.0;
+    let mut chromatic_aberration = 0.0;
+    let mut gamma = 1.0;
+    let mut scanline_intensity = 0.0;
+    let mut scanline_spacing = 0.0;
+    let mut vignette = 0.0;
+
+    for effect in &self.effects {
+      match *effect {
+        Effect::Bloom {
+          intensity,
+          radius,
+          threshold,
+        } => {
+          bloom_intensity = intensity;
+          bloom_radius = radius;
+          bloom_threshold = threshold;
+        }
+        Effect::ChromaticAberration(offset) => chromatic_aberration = offset,
+        Effect::Gamma(value) => gamma = value,
+        Effect::Scanlines { intensity, spacing } => {
+          scanline_intensity = intensity;
+          scanline_spacing = spacing;
+        }
+        Effect::Vignette(strength) => vignette = strength,
+      }
+    }
+
+    for (name, value) in [
+      ("bloom_intensity", bloom_intensity),
+      ("bloom_radius", bloom_radius),
+      ("bloom_threshold", bloom_threshold),
+      ("chromatic_aberration", chromatic_aberration),
+      ("gamma", gamma),
+      ("scanline_intensity", scanline_intensity),
+      ("scanline_spacing", scanline_spacing),
+      ("vignette", vignette),
+    ] {
+      self
+        .display_uniforms
+        .set(&self.gl, name, Uniform::Float(value))?;
+    }
+
+    self
+      .display_uniforms
+      .set(&self.gl, "origin", Uniform::Vec2([x as f32, y as f32]))?;
+
+    self.display_uniforms.set(
+      &self.gl,
+      "extent",
+      Uniform::Vec2([width as f32, height as f32]),
+    )?;
+
+    self.display_uniforms.set(
+      &self.gl,
+      "size",
+      Uniform::Vec2([self.width as f32, self.height as f32]),
+    )?;
+
+    self
+      .gl
+      .bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+
+    self
+      .gl
+      .viewport(0, 0, self.width as i32, self.height as i32);
+
+    self.gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+    self
+      .gl
+      .bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.displayed()));
+
+    self.gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+
+    Ok(())
+  }
 
+  pub(crate) fn sample_audio(&mut self) -> Result {
     self
       .analyser_node
       .get_float_time_domain_data(&mut self.audio_time_domain_data);
@@ -267,7 +474,7 @@ impl Gpu {
     let spl = (sum / self.audio_time_domain_data.len() as f32)
       .sqrt()
       .clamp(0.0, 1.0);
-    self.uniform1f("spl", spl);
+    self.uniform1f("spl", spl)?;
 
     self
       .audio_time_domain_array
@@ -323,116 +530,417 @@ impl Gpu {
         Some(&self.audio_frequency_array),
       )?;
 
-    self.uniform1f("alpha", filter.alpha);
+    self.gl.active_texture(WebGl2RenderingContext::TEXTURE0);
 
-    self.uniform3f(
-      "default_color",
-      filter.default_color[0],
-      filter.default_color[1],
-      filter.default_color[2],
-    );
+    Ok(())
+  }
 
-    self.uniform_matrix4fv("color_transform", &filter.color_transform);
-
-    self.uniform_matrix3fv("position_transform", &filter.position_transform);
-
-    self.uniform1ui("wrap", filter.wrap as u32);
-
-    self.uniform1i(
-      "field",
-      match filter.field {
-        Field::All => 0,
-        Field::Check => 1,
-        Field::Circle => 2,
-        Field::Cross => 3,
-        Field::Equalizer => 4,
-        Field::Frequency => 5,
-        Field::Mod { divisor, remainder } => {
-          self.uniform1ui("mod_divisor", divisor);
-          self.uniform1ui("mod_remainder", remainder);
-          6
-        }
-        Field::Rows { on, off } => {
-          self.uniform1ui("rows_on", on);
-          self.uniform1ui("rows_off", off);
-          7
-        }
-        Field::Square => 8,
-        Field::TimeDomain => 9,
-        Field::Top => 10,
-        Field::Wave => 11,
-        Field::X => 12,
-      },
-    );
+  pub(crate) fn render(&mut self, filter: &Filter) -> Result {
+    self.resize()?;
+
+    self.record(filter);
 
-    self.uniform1ui("coordinates", filter.coordinates as u32);
+    self.rendered = true;
+
+    self.set_filter_uniforms(filter)?;
 
     self.gl.bind_framebuffer(
       WebGl2RenderingContext::FRAMEBUFFER,
       Some(&self.frame_buffer),
     );
 
-    for _ in 0..filter.times {
-      self.gl.framebuffer_texture_2d(
-        WebGl2RenderingContext::FRAMEBUFFER,
-        WebGl2RenderingContext::COLOR_ATTACHMENT0,
-        WebGl2RenderingContext::TEXTURE_2D,
-        Some(&self.destination),
-        0,
+    let times = match &filter.reaction_diffusion {
+      Some(reaction_diffusion) => {
+        self.react(reaction_diffusion, filter.times)?;
+        1
+      }
+      None => filter.times,
+    };
+
+    if filter.sort.is_some() {
+      for _ in 0..times {
+        self.uniform1ui("sort_parity", self.sort_parity)?;
+        Self::draw(&self.gl, &mut self.source, &mut self.destination, 1);
+        self.sort_parity ^= 1;
+      }
+    } else {
+      Self::draw(&self.gl, &mut self.source, &mut self.destination, times);
+    }
+
+    Ok(())
+  }
+
+  fn react(&mut self, reaction_diffusion: &ReactionDiffusion, steps: u32) -> Result {
+    if !self.float_color_buffer {
+      return Err(
+        "Reaction diffusion requires the `EXT_color_buffer_float` WebGL extension".into(),
       );
+    }
 
-      self.gl.active_texture(WebGl2RenderingContext::TEXTURE0);
-      self
-        .gl
-        .bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&self.source));
+    self.gl.use_program(Some(&self.reaction_diffusion_program));
+
+    // Restore the main program even if reacting fails, so that it matches
+    // the state expected by `render`
+    let result = self.react_steps(reaction_diffusion, steps);
+
+    self.gl.use_program(Some(&self.program));
+
+    result
+  }
+
+  fn react_steps(&mut self, reaction_diffusion: &ReactionDiffusion, steps: u32) -> Result {
+    self.reaction_diffusion_uniforms.set(
+      &self.gl,
+      "resolution",
+      Uniform::Float(self.resolution as f32),
+    )?;
+
+    let (mut state, mut next) = match self.chemicals.take() {
+      Some(chemicals) => chemicals,
+      None => {
+        let state =
+          Self::create_texture(&self.gl, self.resolution, WebGl2RenderingContext::RGBA32F)?;
+
+        self
+          .reaction_diffusion_uniforms
+          .set(&self.gl, "seed", Uniform::Int(1))?;
 
-      self.gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+        Self::draw(&self.gl, &mut self.source.clone(), &mut state.clone(), 1);
 
-      mem::swap(&mut self.source, &mut self.destination);
+        (
+          state,
+          Self::create_texture(&self.gl, self.resolution, WebGl2RenderingContext::RGBA32F)?,
+        )
+      }
+    };
+
+    self
+      .reaction_diffusion_uniforms
+      .set(&self.gl, "seed", Uniform::Int(0))?;
+
+    for (name, value) in [
+      ("diffusion_a", reaction_diffusion.diffusion_a),
+      ("diffusion_b", reaction_diffusion.diffusion_b),
+      ("feed", reaction_diffusion.feed),
+      ("kill", reaction_diffusion.kill),
+    ] {
+      self
+        .reaction_diffusion_uniforms
+        .set(&self.gl, name, Uniform::Float(value))?;
     }
 
+    Self::draw(&self.gl, &mut state, &mut next, steps);
+
+    self.gl.active_texture(WebGl2RenderingContext::TEXTURE4);
+    self
+      .gl
+      .bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&state));
+
+    self.chemicals = Some((state, next));
+
     Ok(())
   }
 
-  fn uniform1i(&self, name: &str, value: i32) {
-    self.gl.uniform1i(Some(self.uniform(name)), value);
-  }
+  // Record `filter` in the history used to replay renders when saving tiled
+  // images. Filters that depend on state outside of the canvas, or that
+  // sample across the whole canvas, cannot be replayed one tile at a time, so
+  // rendering them disables the history until the canvas is cleared.
+  fn record(&mut self, filter: &Filter) {
+    if filter.delay > 0
+      || filter.reaction_diffusion.is_some()
+      || filter.sort.is_some()
+      || filter.wrap
+    {
+      self.history = None;
+    }
 
-  fn uniform1ui(&self, name: &str, value: u32) {
-    self.gl.uniform1ui(Some(self.uniform(name)), value);
-  }
+    if let Some(history) = &mut self.history {
+      if let Some(last) = history.last_mut() {
+        if *last
+          == (Filter {
+            times: last.times,
+            ..filter.clone()
+          })
+        {
+          last.times += filter.times;
+          return;
+        }
+      }
 
-  fn uniform1f(&self, name: &str, value: f32) {
-    self.gl.uniform1f(Some(self.uniform(name)), value);
+      if history.len() == Self::MAX_HISTORY {
+        self.history = None;
+      } else {
+        history.push(filter.clone());
+      }
+    }
   }
 
-  fn uniform3f(&self, name: &str, f0: f32, f1: f32, f2: f32) {
-    self.gl.uniform3f(Some(self.uniform(name)), f0, f1, f2);
-  }
+  fn set_filter_uniforms(&mut self, filter: &Filter) -> Result {
+    self.uniform1f("alpha", filter.alpha)?;
+
+    self.uniform3f(
+      "default_color",
+      filter.default_color[0],
+      filter.default_color[1],
+      filter.default_color[2],
+    )?;
+
+    self.uniform_matrix4fv("color_transform", &filter.color_transform)?;
+
+    if filter.color_ops.len() > Self::MAX_COLOR_OPS {
+      return Err(
+        format!(
+          "Filters may have at most {} color operations, not {}",
+          Self::MAX_COLOR_OPS,
+          filter.color_ops.len()
+        )
+        .into(),
+      );
+    }
+
+    let mut ops = vec![0; Self::MAX_COLOR_OPS];
+    let mut parameters = vec![0.0; Self::MAX_COLOR_OPS];
+
+    for (i, op) in filter.color_ops.iter().enumerate() {
+      (ops[i], parameters[i]) = match *op {
+        ColorOp::Contrast(contrast) => (0, contrast),
+        ColorOp::HueRotate(radians) => (1, radians),
+        ColorOp::InvertLuminance => (2, 0.0),
+        ColorOp::Saturation(saturation) => (3, saturation),
+        ColorOp::Value(value) => (4, value),
+      };
+    }
 
-  fn uniform_matrix4fv(&self, name: &str, data: &Matrix4<f32>) {
     self
-      .gl
-      .uniform_matrix4fv_with_f32_array(Some(self.uniform(name)), false, data.as_slice());
+      .uniforms
+      .set(&self.gl, "color_ops[0]", Uniform::IntArray(ops))?;
+    self.uniforms.set(
+      &self.gl,
+      "color_op_parameters[0]",
+      Uniform::FloatArray(parameters),
+    )?;
+    self.uniform1i("color_op_count", filter.color_ops.len().try_into()?)?;
+
+    self.uniform_matrix3fv("position_transform", &filter.position_transform)?;
+
+    self.uniform1ui("wrap", filter.wrap as u32)?;
+
+    self.uniform1ui("linear", filter.linear as u32)?;
+
+    self.uniform1i("blend_mode", Self::blend_mode(filter.blend_mode))?;
+
+    match &filter.curves {
+      Some(curves) => {
+        if self.curves.as_ref() != Some(curves) {
+          let mut table = Vec::with_capacity(Self::LOOKUP_TABLE_SIZE * 4);
+
+          for i in 0..Self::LOOKUP_TABLE_SIZE {
+            let x = i as f32 / (Self::LOOKUP_TABLE_SIZE - 1) as f32;
+            for curve in [&curves.red, &curves.green, &curves.blue, &curves.luminance] {
+              table.push((curve.sample(x).clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+          }
+
+          self.upload_lookup_table(
+            WebGl2RenderingContext::TEXTURE7,
+            &self.curves_texture,
+            &table,
+          )?;
+
+          self.curves = Some(curves.clone());
+        }
+
+        self.gl.active_texture(WebGl2RenderingContext::TEXTURE7);
+        self.gl.bind_texture(
+          WebGl2RenderingContext::TEXTURE_2D,
+          Some(&self.curves_texture),
+        );
+
+        self.uniform1ui("curves", 1)?;
+      }
+      None => self.uniform1ui("curves", 0)?,
+    }
+
+    match filter.posterize {
+      Some(levels) => {
+        if levels.contains(&0) {
+          return Err("Posterize levels must be greater than zero".into());
+        }
+        self.uniform1ui("posterize", 1)?;
+        self.uniform3f(
+          "posterize_levels",
+          levels[0] as f32,
+          levels[1] as f32,
+          levels[2] as f32,
+        )?;
+      }
+      None => self.uniform1ui("posterize", 0)?,
+    }
+
+    match filter.threshold {
+      Some(threshold) => {
+        self.uniform1ui("threshold", 1)?;
+        self.uniform1f("threshold_value", threshold)?;
+      }
+      None => self.uniform1ui("threshold", 0)?,
+    }
+
+    let dither = match filter.dither {
+      Some(Dither::Bayer) => 1,
+      Some(Dither::BlueNoise) => {
+        self.gl.active_texture(WebGl2RenderingContext::TEXTURE6);
+        self.gl.bind_texture(
+          WebGl2RenderingContext::TEXTURE_2D,
+          Some(&self.blue_noise_texture),
+        );
+        2
+      }
+      None => 0,
+    };
+
+    self.uniform1i("dither", dither)?;
+
+    match filter.gradient_map {
+      Some(channel) => {
+        self.gl.active_texture(WebGl2RenderingContext::TEXTURE5);
+        self.gl.bind_texture(
+          WebGl2RenderingContext::TEXTURE_2D,
+          Some(&self.palette_texture),
+        );
+        self.uniform1ui("gradient_map", 1)?;
+        self.uniform1i("gradient_map_channel", Self::channel(channel))?;
+      }
+      None => self.uniform1ui("gradient_map", 0)?,
+    }
+
+    let field = match filter.field {
+      Field::All => 0,
+      Field::Check => 1,
+      Field::Circle => 2,
+      Field::Cross => 3,
+      Field::Equalizer => 4,
+      Field::Frequency => 5,
+      Field::Mod { divisor, remainder } => {
+        self.uniform1ui("mod_divisor", divisor)?;
+        self.uniform1ui("mod_remainder", remainder)?;
+        6
+      }
+      Field::Rows { on, off } => {
+        self.uniform1ui("rows_on", on)?;
+        self.uniform1ui("rows_off", off)?;
+        7
+      }
+      Field::Square => 8,
+      Field::TimeDomain => 9,
+      Field::Top => 10,
+      Field::Wave => 11,
+      Field::X => 12,
+    };
+
+    self.uniform1i("field", field)?;
+
+    self.uniform1ui("coordinates", filter.coordinates as u32)?;
+
+    match &filter.kernel {
+      Some(kernel) => {
+        let weights = kernel.weights();
+
+        let size = match weights.len() {
+          9 => 3,
+          25 => 5,
+          len => return Err(format!("Kernels must have 9 or 25 weights, not {len}").into()),
+        };
+
+        let mut values = vec![0.0; 25];
+        values[..weights.len()].copy_from_slice(weights);
+
+        self
+          .uniforms
+          .set(&self.gl, "kernel[0]", Uniform::FloatArray(values))?;
+        self.uniform1i("kernel_size", size)?;
+      }
+      None => self.uniform1i("kernel_size", 0)?,
+    }
+
+    match &filter.automaton {
+      Some(automaton) => {
+        let rule = automaton.rule.parse::<Rule>()?;
+        self.uniform1ui("automaton", 1)?;
+        self.uniform1ui("automaton_birth", rule.birth)?;
+        self.uniform1ui("automaton_survival", rule.survival)?;
+        self.uniform1i("automaton_channel", Self::channel(automaton.channel))?;
+        self.uniform1f("automaton_threshold", automaton.threshold)?;
+      }
+      None => self.uniform1ui("automaton", 0)?,
+    }
+
+    match filter.sort {
+      Some(sort) => {
+        let (axis, ascending) = match sort.direction {
+          Direction::Down => (1, false),
+          Direction::Left => (0, false),
+          Direction::Right => (0, true),
+          Direction::Up => (1, true),
+        };
+        self.uniform1ui("sort", 1)?;
+        self.uniform1i("sort_axis", axis)?;
+        self.uniform1ui("sort_ascending", ascending as u32)?;
+        self.uniform1f("sort_threshold", sort.threshold)?;
+      }
+      None => self.uniform1ui("sort", 0)?,
+    }
+
+    self.uniform1ui(
+      "reaction_diffusion",
+      filter.reaction_diffusion.is_some() as u32,
+    )?;
+
+    let delay = filter.delay as usize;
+
+    self.delay = self.delay.max(delay);
+
+    if delay > 0 && !self.rewind.is_empty() {
+      self.gl.active_texture(WebGl2RenderingContext::TEXTURE3);
+      self.gl.bind_texture(
+        WebGl2RenderingContext::TEXTURE_2D,
+        Some(&self.rewind[delay.min(self.rewind.len() - 1)]),
+      );
+      self.uniform1ui("delay", 1)?;
+    } else {
+      self.uniform1ui("delay", 0)?;
+    }
+
+    Ok(())
   }
 
-  fn uniform_matrix3fv(&self, name: &str, data: &Matrix3<f32>) {
-    self
-      .gl
-      .uniform_matrix3fv_with_f32_array(Some(self.uniform(name)), false, data.as_slice());
+  fn blend_mode(blend_mode: BlendMode) -> i32 {
+    match blend_mode {
+      BlendMode::Add => 0,
+      BlendMode::Difference => 1,
+      BlendMode::Max => 2,
+      BlendMode::Min => 3,
+      BlendMode::Multiply => 4,
+      BlendMode::Normal => 5,
+      BlendMode::Overlay => 6,
+      BlendMode::Screen => 7,
+      BlendMode::Xor => 8,
+    }
   }
 
-  fn create_texture(gl: &WebGl2RenderingContext, resolution: u32) -> Result<WebGlTexture> {
-    let texture = gl.create_texture().ok_or("Failed to create texture")?;
+  fn create_lookup_table(gl: &WebGl2RenderingContext) -> Result<WebGlTexture> {
+    let texture = gl
+      .create_texture()
+      .ok_or("Failed to create lookup table texture")?;
 
+    gl.active_texture(WebGl2RenderingContext::TEXTURE0);
     gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
 
     gl.tex_storage_2d(
       WebGl2RenderingContext::TEXTURE_2D,
       1,
       WebGl2RenderingContext::RGBA8,
-      resolution as i32,
-      resolution as i32,
+      Self::LOOKUP_TABLE_SIZE as i32,
+      1,
     );
 
     gl.tex_parameteri(
@@ -450,39 +958,275 @@ impl Gpu {
     Ok(texture)
   }
 
-  pub(crate) fn lock_resolution(&mut self, resolution: u32) {
-    self.width = resolution;
-    self.height = resolution;
-    self.resolution = resolution;
-    self.lock_resolution = true;
-  }
-
-  pub(crate) fn resize(&mut self) -> Result {
-    if self.lock_resolution {
-      if self.canvas.height() == self.height && self.canvas.width() == self.width {
-        return Ok(());
-      }
-    } else {
-      let css_pixel_height: f64 = self.canvas.client_height().into();
-      let css_pixel_width: f64 = self.canvas.client_width().into();
-
-      let device_pixel_ratio = self.window.device_pixel_ratio();
-      let device_pixel_height = (css_pixel_height * device_pixel_ratio).ceil() as u32;
-      let device_pixel_width = (css_pixel_width * device_pixel_ratio).ceil() as u32;
+  // Upload `table` to `texture`, binding it on the texture unit `unit`, which
+  // should be the unit it is sampled from
+  fn upload_lookup_table(&self, unit: u32, texture: &WebGlTexture, table: &[u8]) -> Result {
+    self.gl.active_texture(unit);
+    self
+      .gl
+      .bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(texture));
 
-      if self.canvas.height() == device_pixel_height && self.canvas.width() == device_pixel_width {
-        return Ok(());
+    self
+      .gl
+      .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+        WebGl2RenderingContext::TEXTURE_2D,
+        0,
+        0,
+        0,
+        Self::LOOKUP_TABLE_SIZE as i32,
+        1,
+        WebGl2RenderingContext::RGBA,
+        WebGl2RenderingContext::UNSIGNED_BYTE,
+        Some(table),
+      )?;
+
+    Ok(())
+  }
+
+  pub(crate) fn set_palette(&mut self, palette: Palette) -> Result {
+    // Filters in the history were rendered with the previous palette, so they
+    // cannot be replayed with this one
+    if palette != self.palette && self.history.as_ref().is_some_and(|h| !h.is_empty()) {
+      self.history = None;
+    }
+
+    let mut table = Vec::with_capacity(Self::LOOKUP_TABLE_SIZE * 4);
+
+    for i in 0..Self::LOOKUP_TABLE_SIZE {
+      let color = palette
+        .sample(i as f32 / (Self::LOOKUP_TABLE_SIZE - 1) as f32)
+        .ok_or("Palettes must have at least one color")?;
+
+      for channel in color {
+        table.push((channel.clamp(0.0, 1.0) * 255.0).round() as u8);
       }
 
-      self.width = device_pixel_width;
-      self.height = device_pixel_height;
-      self.resolution = self.width.max(self.height);
+      table.push(255);
+    }
+
+    // While the context is lost, the palette is uploaded when it is restored
+    if !self.context_lost() {
+      self.upload_lookup_table(
+        WebGl2RenderingContext::TEXTURE5,
+        &self.palette_texture,
+        &table,
+      )?;
+    }
+
+    self.palette = palette;
+
+    Ok(())
+  }
+
+  fn channel(channel: Channel) -> i32 {
+    match channel {
+      Channel::Blue => 0,
+      Channel::Green => 1,
+      Channel::Luminance => 2,
+      Channel::Red => 3,
+    }
+  }
+
+  fn draw(
+    gl: &WebGl2RenderingContext,
+    source: &mut WebGlTexture,
+    destination: &mut WebGlTexture,
+    times: u32,
+  ) {
+    for _ in 0..times {
+      gl.framebuffer_texture_2d(
+        WebGl2RenderingContext::FRAMEBUFFER,
+        WebGl2RenderingContext::COLOR_ATTACHMENT0,
+        WebGl2RenderingContext::TEXTURE_2D,
+        Some(destination),
+        0,
+      );
+
+      gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+      gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(source));
+
+      gl.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, 3);
+
+      mem::swap(source, destination);
     }
+  }
+
+  fn uniform1i(&mut self, name: &str, value: i32) -> Result {
+    self.uniforms.set(&self.gl, name, Uniform::Int(value))
+  }
+
+  fn uniform1ui(&mut self, name: &str, value: u32) -> Result {
+    self
+      .uniforms
+      .set(&self.gl, name, Uniform::UnsignedInt(value))
+  }
+
+  fn uniform1f(&mut self, name: &str, value: f32) -> Result {
+    self.uniforms.set(&self.gl, name, Uniform::Float(value))
+  }
+
+  fn uniform2f(&mut self, name: &str, f0: f32, f1: f32) -> Result {
+    self.uniforms.set(&self.gl, name, Uniform::Vec2([f0, f1]))
+  }
+
+  fn uniform3f(&mut self, name: &str, f0: f32, f1: f32, f2: f32) -> Result {
+    self
+      .uniforms
+      .set(&self.gl, name, Uniform::Vec3([f0, f1, f2]))
+  }
+
+  fn uniform_matrix4fv(&mut self, name: &str, data: &Matrix4<f32>) -> Result {
+    self.uniforms.set(&self.gl, name, Uniform::Mat4(*data))
+  }
+
+  fn uniform_matrix3fv(&mut self, name: &str, data: &Matrix3<f32>) -> Result {
+    self.uniforms.set(&self.gl, name, Uniform::Mat3(*data))
+  }
+
+  fn create_program(gl: &WebGl2RenderingContext, fragment_source: &str) -> Result<WebGlProgram> {
+    let program = gl.create_program().ok_or("Failed to create program")?;
+
+    let vertex = gl
+      .create_shader(WebGl2RenderingContext::VERTEX_SHADER)
+      .ok_or("Failed to create shader")?;
+
+    gl.shader_source(&vertex, include_str!("vertex.glsl"));
+    gl.compile_shader(&vertex);
+
+    if !gl.get_shader_parameter(&vertex, WebGl2RenderingContext::COMPILE_STATUS) {
+      return Err(
+        gl.get_shader_info_log(&vertex)
+          .ok_or("Failed to get shader info log")?
+          .into(),
+      );
+    }
+
+    let fragment = gl
+      .create_shader(WebGl2RenderingContext::FRAGMENT_SHADER)
+      .ok_or("Failed to create shader")?;
+
+    gl.shader_source(&fragment, fragment_source);
+    gl.compile_shader(&fragment);
+
+    if !gl.get_shader_parameter(&fragment, WebGl2RenderingContext::COMPILE_STATUS) {
+      return Err(
+        gl.get_shader_info_log(&fragment)
+          .ok_or("Failed to get shader info log")?
+          .into(),
+      );
+    }
+
+    gl.attach_shader(&program, &vertex);
+    gl.attach_shader(&program, &fragment);
+
+    gl.link_program(&program);
+
+    if !gl.get_program_parameter(&program, WebGl2RenderingContext::LINK_STATUS) {
+      return Err(
+        gl.get_program_info_log(&program)
+          .ok_or("Failed to get program log info")?
+          .into(),
+      );
+    }
+
+    Ok(program)
+  }
+
+  fn create_texture(
+    gl: &WebGl2RenderingContext,
+    resolution: u32,
+    format: u32,
+  ) -> Result<WebGlTexture> {
+    let texture = gl.create_texture().ok_or("Failed to create texture")?;
+
+    // Texture unit 0 is used for setup, since the source is bound to it
+    // before every draw, so binding there does not disturb the textures bound
+    // to the other units for sampling
+    gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+    gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+
+    gl.tex_storage_2d(
+      WebGl2RenderingContext::TEXTURE_2D,
+      1,
+      format,
+      resolution as i32,
+      resolution as i32,
+    );
+
+    gl.tex_parameteri(
+      WebGl2RenderingContext::TEXTURE_2D,
+      WebGl2RenderingContext::TEXTURE_MIN_FILTER,
+      WebGl2RenderingContext::NEAREST.try_into()?,
+    );
+
+    gl.tex_parameteri(
+      WebGl2RenderingContext::TEXTURE_2D,
+      WebGl2RenderingContext::TEXTURE_MAG_FILTER,
+      WebGl2RenderingContext::NEAREST.try_into()?,
+    );
+
+    Ok(texture)
+  }
+
+  pub(crate) fn lock_resolution(&mut self, resolution: u32) -> Result {
+    self.lock_resolution = true;
+
+    // While the context is lost, the resolution is set when it is restored
+    if self.context_lost() {
+      self.resolution = resolution;
+      return Ok(());
+    }
+
+    self.set_resolution(resolution)?;
+    self.present()
+  }
+
+  pub(crate) fn size(&self) -> (u32, u32) {
+    (self.width, self.height)
+  }
+
+  pub(crate) fn resize(&mut self) -> Result {
+    let css_pixel_height: f64 = self.canvas.client_height().into();
+    let css_pixel_width: f64 = self.canvas.client_width().into();
+
+    let device_pixel_ratio = self.window.device_pixel_ratio();
+    let device_pixel_height = (css_pixel_height * device_pixel_ratio).ceil() as u32;
+    let device_pixel_width = (css_pixel_width * device_pixel_ratio).ceil() as u32;
+
+    if self.canvas.height() == device_pixel_height && self.canvas.width() == device_pixel_width {
+      return Ok(());
+    }
+
+    self.width = device_pixel_width;
+    self.height = device_pixel_height;
 
     self.canvas.set_height(self.height);
     self.canvas.set_width(self.width);
 
-    self.uniform1f("resolution", self.resolution as f32);
+    if !self.lock_resolution {
+      self.set_resolution(self.width.max(self.height))?;
+    }
+
+    self.present()?;
+
+    Ok(())
+  }
+
+  fn set_resolution(&mut self, resolution: u32) -> Result {
+    if resolution == self.resolution {
+      return Ok(());
+    }
+
+    self.resolution = resolution;
+
+    for texture in self.rewind.drain(..) {
+      self.gl.delete_texture(Some(&texture));
+    }
+
+    self.rewind_position = 0;
+
+    self.uniform1f("resolution", self.resolution as f32)?;
+    self.uniform1f("tile_resolution", self.resolution as f32)?;
 
     self
       .gl
@@ -490,27 +1234,171 @@ impl Gpu {
 
     self.clear()?;
 
-    self.present()?;
+    Ok(())
+  }
+
+  // Set the precision of the source and destination textures, falling back
+  // to 8-bit textures if float textures cannot be rendered to. Changing
+  // precision clears the canvas and the rewind buffer.
+  pub(crate) fn set_precision(&mut self, precision: Precision) -> Result {
+    let precision = if precision != Precision::Byte && !self.float_color_buffer {
+      log::warn!(
+        "Float textures require the `EXT_color_buffer_float` WebGL extension, falling back to byte precision"
+      );
+      Precision::Byte
+    } else {
+      precision
+    };
+
+    if precision == self.precision {
+      return Ok(());
+    }
+
+    self.precision = precision;
+
+    // While the context is lost, precision is set when it is restored
+    if self.context_lost() {
+      return Ok(());
+    }
+
+    for texture in self.rewind.drain(..) {
+      self.gl.delete_texture(Some(&texture));
+    }
+
+    self.rewind_position = 0;
+
+    self.clear()?;
+
+    self.present()
+  }
+
+  fn format(&self) -> u32 {
+    match self.precision {
+      Precision::Byte => WebGl2RenderingContext::RGBA8,
+      Precision::Float => WebGl2RenderingContext::RGBA32F,
+      Precision::Half => WebGl2RenderingContext::RGBA16F,
+    }
+  }
+
+  pub(crate) fn set_scaling(&mut self, scaling: Scaling) -> Result {
+    self.scaling = scaling;
+    self.present()
+  }
+
+  // The number of frames to keep in the rewind buffer. Frames are captured
+  // after rendering, so the most recent frame is the current canvas, and
+  // sampling a frame `delay` frames ago requires `delay + 1` frames.
+  fn rewind_len(&self) -> usize {
+    if self.delay > 0 {
+      self.rewind_capacity.max(self.delay + 1)
+    } else {
+      self.rewind_capacity
+    }
+  }
+
+  pub(crate) fn capture(&mut self) -> Result {
+    let capacity = self.rewind_len();
+
+    if !self.rendered || capacity == 0 || self.rewind_position > 0 {
+      return Ok(());
+    }
+
+    self.rendered = false;
+
+    while self.rewind.len() > capacity {
+      if let Some(texture) = self.rewind.pop_back() {
+        self.gl.delete_texture(Some(&texture));
+      }
+    }
+
+    let texture = if self.rewind.len() < capacity {
+      Self::create_texture(&self.gl, self.resolution, self.format())?
+    } else {
+      self.rewind.pop_back().ok_or("Rewind buffer is empty")?
+    };
+
+    self.copy_texture(&self.source, &texture);
+
+    self.rewind.push_front(texture);
 
     Ok(())
   }
 
-  pub(crate) fn save_image(&self) -> Result<ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+  fn copy_texture(&self, from: &WebGlTexture, to: &WebGlTexture) {
     self.gl.bind_framebuffer(
-      WebGl2RenderingContext::FRAMEBUFFER,
+      WebGl2RenderingContext::READ_FRAMEBUFFER,
       Some(&self.frame_buffer),
     );
 
-    let mut array = vec![0; (self.resolution * self.resolution * 4) as usize];
-    self.gl.read_pixels_with_opt_u8_array(
+    self.gl.framebuffer_texture_2d(
+      WebGl2RenderingContext::READ_FRAMEBUFFER,
+      WebGl2RenderingContext::COLOR_ATTACHMENT0,
+      WebGl2RenderingContext::TEXTURE_2D,
+      Some(from),
+      0,
+    );
+
+    self.gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+    self
+      .gl
+      .bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(to));
+
+    self.gl.copy_tex_sub_image_2d(
+      WebGl2RenderingContext::TEXTURE_2D,
+      0,
+      0,
+      0,
       0,
       0,
       self.resolution as i32,
       self.resolution as i32,
-      WebGl2RenderingContext::RGBA,
-      WebGl2RenderingContext::UNSIGNED_BYTE,
-      Some(&mut array),
-    )?;
+    );
+  }
+
+  pub(crate) fn set_rewind_capacity(&mut self, capacity: u32) -> Result {
+    self.rewind_capacity = capacity.try_into()?;
+
+    while self.rewind.len() > self.rewind_len() {
+      if let Some(texture) = self.rewind.pop_back() {
+        self.gl.delete_texture(Some(&texture));
+      }
+    }
+
+    self.rewind(self.rewind_position)
+  }
+
+  pub(crate) fn rewind(&mut self, position: usize) -> Result {
+    self.rewind_position = position.min(self.rewind.len().saturating_sub(1));
+    self.present()
+  }
+
+  pub(crate) fn step_back(&mut self) -> Result {
+    self.rewind(self.rewind_position + 1)
+  }
+
+  pub(crate) fn step_forward(&mut self) -> Result {
+    self.rewind(self.rewind_position.saturating_sub(1))
+  }
+
+  pub(crate) fn restore_rewind(&mut self) -> Result {
+    if self.rewind_position == 0 {
+      return Ok(());
+    }
+
+    self.copy_texture(&self.rewind[self.rewind_position], &self.source);
+
+    self.history = None;
+
+    self.rewind(0)
+  }
+
+  pub(crate) fn save_image(&self) -> Result<ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+    self.gl.bind_framebuffer(
+      WebGl2RenderingContext::FRAMEBUFFER,
+      Some(&self.frame_buffer),
+    );
+
+    let array = self.read_pixels(0, 0, self.resolution, self.resolution)?;
 
     let image = ImageBuffer::from_raw(self.resolution, self.resolution, array)
       .ok_or("Failed to create ImageBuffer")?;
@@ -518,17 +1406,339 @@ impl Gpu {
     Ok(image)
   }
 
-  pub(crate) fn set_decibel_range(&mut self, min: f32, max: f32) {
-    self.decibels_min = min;
-    self.decibels_max = max;
+  // Read pixels from the current framebuffer as 8-bit RGBA. Float
+  // framebuffers can only be read as floats, so are read as floats and
+  // converted.
+  fn read_pixels(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>> {
+    let len = width * height * 4;
+
+    if self.precision == Precision::Byte {
+      let mut array = vec![0; len as usize];
+      self.gl.read_pixels_with_opt_u8_array(
+        x as i32,
+        y as i32,
+        width as i32,
+        height as i32,
+        WebGl2RenderingContext::RGBA,
+        WebGl2RenderingContext::UNSIGNED_BYTE,
+        Some(&mut array),
+      )?;
+      return Ok(array);
+    }
+
+    let array = Float32Array::new_with_length(len);
+    self.gl.read_pixels_with_opt_array_buffer_view(
+      x as i32,
+      y as i32,
+      width as i32,
+      height as i32,
+      WebGl2RenderingContext::RGBA,
+      WebGl2RenderingContext::FLOAT,
+      Some(&array),
+    )?;
+
+    Ok(
+      array
+        .to_vec()
+        .into_iter()
+        .map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+        .collect(),
+    )
+  }
+
+  pub(crate) fn context_lost(&self) -> bool {
+    self.gl.is_context_lost()
   }
 
-  fn uniform(&self, name: &str) -> &WebGlUniformLocation {
+  pub(crate) fn restore(&mut self) -> Result {
+    let mut gpu = Self::with_context(
+      self.gl.clone(),
+      &self.window,
+      &self.canvas,
+      &self.analyser_node,
+    )?;
+
+    gpu.decibels_max = self.decibels_max;
+    gpu.decibels_min = self.decibels_min;
+    gpu.effects = mem::take(&mut self.effects);
+    gpu.rewind_capacity = self.rewind_capacity;
+    gpu.scaling = self.scaling;
+    gpu.snapshot = self.snapshot.take();
+
+    if self.lock_resolution {
+      gpu.lock_resolution = true;
+      gpu.set_resolution(self.resolution)?;
+    }
+
+    gpu.set_precision(self.precision)?;
+
+    gpu.set_palette(self.palette.clone())?;
+
+    if let Some(snapshot) = &gpu.snapshot {
+      if snapshot.width() == gpu.resolution && snapshot.height() == gpu.resolution {
+        gpu.gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+        gpu
+          .gl
+          .bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&gpu.source));
+        if gpu.precision == Precision::Byte {
+          gpu
+            .gl
+            .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+              WebGl2RenderingContext::TEXTURE_2D,
+              0,
+              0,
+              0,
+              gpu.resolution as i32,
+              gpu.resolution as i32,
+              WebGl2RenderingContext::RGBA,
+              WebGl2RenderingContext::UNSIGNED_BYTE,
+              Some(snapshot.as_raw()),
+            )?;
+        } else {
+          let array = Float32Array::from(
+            snapshot
+              .as_raw()
+              .iter()
+              .map(|value| *value as f32 / 255.0)
+              .collect::<Vec<f32>>()
+              .as_slice(),
+          );
+          gpu
+            .gl
+            .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+              WebGl2RenderingContext::TEXTURE_2D,
+              0,
+              0,
+              0,
+              gpu.resolution as i32,
+              gpu.resolution as i32,
+              WebGl2RenderingContext::RGBA,
+              WebGl2RenderingContext::FLOAT,
+              Some(&array),
+            )?;
+        }
+        gpu.history = None;
+      }
+    }
+
+    *self = gpu;
+
+    self.present()
+  }
+
+  pub(crate) fn set_snapshot(&mut self, snapshot: ImageBuffer<image::Rgba<u8>, Vec<u8>>) {
+    self.snapshot = Some(snapshot);
+  }
+
+  pub(crate) fn save_tiled_image(
+    &mut self,
+    resolution: u32,
+  ) -> Result<ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+    if resolution == 0 || resolution > Self::MAX_TILED_RESOLUTION {
+      return Err(
+        format!(
+          "Tiled image resolution must be between 1 and {}, not {resolution}",
+          Self::MAX_TILED_RESOLUTION
+        )
+        .into(),
+      );
+    }
+
+    let history = self.history.clone().ok_or(
+      "Filter history is unavailable, call `clear()` before rendering to save tiled images. \
+      History is also unavailable after rendering with `delay`, `reactionDiffusion`, `sort`, or \
+      `wrap`, after changing the palette, and after restoring a rewound frame.",
+    )?;
+
+    let max_texture_size = self
+      .gl
+      .get_parameter(WebGl2RenderingContext::MAX_TEXTURE_SIZE)?
+      .as_f64()
+      .ok_or("Failed to get max texture size")? as u32;
+
+    let tile_resolution = Self::TILE_RESOLUTION.min(max_texture_size);
+
+    // Each tile is rendered with a margin of overlapping pixels around it, so
+    // that filters that sample outside of the tile can see the pixels they
+    // need. The overlap required is the furthest distance, in pixels, that
+    // samples are moved by all filters in the history.
+    let mut distance = 0.0;
+    for filter in &history {
+      let mut displacement = 0.0f32;
+      for corner in [[-1.0, -1.0], [-1.0, 1.0], [1.0, -1.0], [1.0, 1.0]] {
+        let position = Vector3::new(corner[0], corner[1], 1.0);
+        let transformed = filter.position_transform * position;
+        displacement = displacement.max((transformed - position).xy().norm());
+      }
+      let radius = if filter.automaton.is_some() {
+        1.0
+      } else {
+        match filter.kernel.as_ref().map(|kernel| kernel.weights().len()) {
+          Some(25) => 2.0,
+          Some(_) => 1.0,
+          None => 0.0,
+        }
+      };
+      distance += (displacement * resolution as f32 / 2.0 + 1.0 + radius) * filter.times as f32;
+    }
+
+    let max_overlap = tile_resolution / 4;
+    let overlap = distance.ceil() as u32;
+
+    if overlap > max_overlap {
+      return Err(
+        format!(
+          "Tiled image requires an overlap of {overlap} pixels, which exceeds the maximum of {max_overlap} pixels"
+        )
+        .into(),
+      );
+    }
+
+    let mut source = Self::create_texture(&self.gl, tile_resolution, self.format())?;
+    let mut destination = Self::create_texture(&self.gl, tile_resolution, self.format())?;
+
+    for texture in [&source, &destination] {
+      self
+        .gl
+        .bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(texture));
+
+      for parameter in [
+        WebGl2RenderingContext::TEXTURE_WRAP_S,
+        WebGl2RenderingContext::TEXTURE_WRAP_T,
+      ] {
+        self.gl.tex_parameteri(
+          WebGl2RenderingContext::TEXTURE_2D,
+          parameter,
+          WebGl2RenderingContext::CLAMP_TO_EDGE.try_into()?,
+        );
+      }
+    }
+
+    let result = self.render_tiles(
+      &history,
+      resolution,
+      tile_resolution,
+      overlap,
+      &mut source,
+      &mut destination,
+    );
+
+    self.gl.delete_texture(Some(&source));
+    self.gl.delete_texture(Some(&destination));
+
+    self.uniform1f("resolution", self.resolution as f32)?;
+    self.uniform1f("tile_resolution", self.resolution as f32)?;
+    self.uniform2f("tile_offset", 0.0, 0.0)?;
+
     self
-      .uniforms
-      .get(name)
-      .ok_or_else(|| format!("Uniform `{name}` is missing.",))
-      .unwrap()
+      .gl
+      .viewport(0, 0, self.resolution as i32, self.resolution as i32);
+
+    self.gl.framebuffer_texture_2d(
+      WebGl2RenderingContext::FRAMEBUFFER,
+      WebGl2RenderingContext::COLOR_ATTACHMENT0,
+      WebGl2RenderingContext::TEXTURE_2D,
+      Some(&self.source),
+      0,
+    );
+
+    result
+  }
+
+  // Render each tile of a tiled image by replaying `history`, copying the
+  // core of each tile, without its overlapping margin, into the image.
+  fn render_tiles(
+    &mut self,
+    history: &[Filter],
+    resolution: u32,
+    tile_resolution: u32,
+    overlap: u32,
+    source: &mut WebGlTexture,
+    destination: &mut WebGlTexture,
+  ) -> Result<ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+    let core = tile_resolution - overlap * 2;
+
+    let mut image = ImageBuffer::new(resolution, resolution);
+
+    self.gl.bind_framebuffer(
+      WebGl2RenderingContext::FRAMEBUFFER,
+      Some(&self.frame_buffer),
+    );
+
+    self
+      .gl
+      .viewport(0, 0, tile_resolution as i32, tile_resolution as i32);
+
+    self.uniform1f("resolution", resolution as f32)?;
+    self.uniform1f("tile_resolution", tile_resolution as f32)?;
+
+    for y in (0..resolution).step_by(core as usize) {
+      for x in (0..resolution).step_by(core as usize) {
+        for texture in [&*source, &*destination] {
+          self.gl.framebuffer_texture_2d(
+            WebGl2RenderingContext::FRAMEBUFFER,
+            WebGl2RenderingContext::COLOR_ATTACHMENT0,
+            WebGl2RenderingContext::TEXTURE_2D,
+            Some(texture),
+            0,
+          );
+
+          self.gl.clear_bufferfv_with_f32_array(
+            WebGl2RenderingContext::COLOR,
+            0,
+            &[0.0, 0.0, 0.0, 0.0],
+          );
+        }
+
+        self.uniform2f(
+          "tile_offset",
+          x as f32 - overlap as f32,
+          y as f32 - overlap as f32,
+        )?;
+
+        for filter in history {
+          self.set_filter_uniforms(filter)?;
+          Self::draw(&self.gl, source, destination, filter.times);
+        }
+
+        self.gl.framebuffer_texture_2d(
+          WebGl2RenderingContext::FRAMEBUFFER,
+          WebGl2RenderingContext::COLOR_ATTACHMENT0,
+          WebGl2RenderingContext::TEXTURE_2D,
+          Some(source),
+          0,
+        );
+
+        let width = core.min(resolution - x);
+        let height = core.min(resolution - y);
+
+        let pixels = self.read_pixels(overlap, overlap, width, height)?;
+
+        for row in 0..height {
+          for column in 0..width {
+            let i = ((row * width + column) * 4) as usize;
+            image.put_pixel(
+              x + column,
+              y + row,
+              image::Rgba([pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]),
+            );
+          }
+        }
+      }
+    }
+
+    Ok(image)
+  }
+
+  pub(crate) fn set_effects(&mut self, effects: Vec<Effect>) -> Result {
+    Effect::validate(&effects)?;
+    self.effects = effects;
+    Ok(())
+  }
+
+  pub(crate) fn set_decibel_range(&mut self, min: f32, max: f32) {
+    self.decibels_min = min;
+    self.decibels_max = max;
   }
 
   pub(crate) fn clear(&mut self) -> Result {
@@ -539,8 +1749,17 @@ impl Gpu {
     self.gl.delete_texture(Some(&self.source));
     self.gl.delete_texture(Some(&self.destination));
 
-    self.source = Self::create_texture(&self.gl, self.resolution)?;
-    self.destination = Self::create_texture(&self.gl, self.resolution)?;
+    self.source = Self::create_texture(&self.gl, self.resolution, self.format())?;
+    self.destination = Self::create_texture(&self.gl, self.resolution, self.format())?;
+
+    if let Some((state, next)) = self.chemicals.take() {
+      self.gl.delete_texture(Some(&state));
+      self.gl.delete_texture(Some(&next));
+    }
+
+    self.history = Some(Vec::new());
+
+    self.delay = 0;
 
     Ok(())
   }
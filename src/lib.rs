@@ -1,11 +1,29 @@
 use {
   serde::{Deserialize, Serialize},
-  std::cell::RefCell,
+  std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    mem,
+    ops::Mul,
+    rc::Rc,
+    str::FromStr,
+  },
   wasm_bindgen::{closure::Closure, JsCast, JsValue},
   web_sys::{DedicatedWorkerGlobalScope, MessageEvent},
 };
 
-pub use std::f32::consts::TAU;
+pub use {
+  color_transform::ColorTransform,
+  combinators::{cycle, Crossfade, Cycle, Layer, Sequence},
+  std::f32::consts::TAU,
+  timeline::{Cue, Show, Timeline, Transition, Trigger},
+};
+
+use combinators::with_alpha;
+
+mod color_transform;
+mod combinators;
+mod timeline;
 
 pub type Matrix3 = nalgebra::Matrix3<f32>;
 pub type Matrix4 = nalgebra::Matrix4<f32>;
@@ -15,9 +33,12 @@ pub type Scale2 = nalgebra::Scale2<f32>;
 pub type Similarity2 = nalgebra::Similarity2<f32>;
 pub type Similarity3 = nalgebra::Similarity3<f32>;
 pub type Translation2 = nalgebra::Translation2<f32>;
+pub type Vector2 = nalgebra::Vector2<f32>;
 pub type Vector3 = nalgebra::Vector3<f32>;
 
 thread_local! {
+  static ALPHA: Cell<f32> = const { Cell::new(1.0) };
+  static PROCESSES: RefCell<Processes> = RefCell::new(Processes::default());
   static SYSTEM: RefCell<System> = RefCell::new(System::new());
 }
 
@@ -25,6 +46,130 @@ pub fn send(message: Message) {
   SYSTEM.with(|system| system.borrow_mut().send(message));
 }
 
+pub fn clear() {
+  send(Message::Clear);
+}
+
+pub fn reboot() -> Filter {
+  clear();
+  Filter::new()
+}
+
+pub fn resolution(resolution: u32) {
+  send(Message::Resolution(resolution));
+}
+
+pub fn save() {
+  send(Message::Save);
+}
+
+pub fn save_tiled(resolution: u32) {
+  send(Message::SaveTiled(resolution));
+}
+
+pub fn snapshot() {
+  send(Message::Snapshot);
+}
+
+pub fn record() {
+  send(Message::Record);
+}
+
+pub fn decibel_range(min: f32, max: f32) {
+  send(Message::DecibelRange { min, max });
+}
+
+pub fn oscillator_gain(gain: f32) {
+  send(Message::OscillatorGain(gain));
+}
+
+pub fn oscillator_frequency(frequency: f32) {
+  send(Message::OscillatorFrequency(frequency));
+}
+
+pub fn effects(effects: Vec<Effect>) {
+  match Effect::validate(&effects) {
+    Ok(()) => send(Message::Effects(effects)),
+    Err(err) => error(err),
+  }
+}
+
+pub fn palette(palette: Palette) {
+  send(Message::Palette(palette));
+}
+
+pub fn precision(precision: Precision) {
+  send(Message::Precision(precision));
+}
+
+pub fn scaling(scaling: Scaling) {
+  send(Message::Scaling(scaling));
+}
+
+pub fn rewind(frames: u32) {
+  send(Message::Rewind(frames));
+}
+
+pub fn rewind_capacity(capacity: u32) {
+  send(Message::RewindCapacity(capacity));
+}
+
+pub fn restore_rewind() {
+  send(Message::RestoreRewind);
+}
+
+pub fn step_back() {
+  send(Message::StepBack);
+}
+
+pub fn step_forward() {
+  send(Message::StepForward);
+}
+
+pub fn checkbox(name: &str) -> bool {
+  widget(name, Widget::Checkbox)
+    .and_then(|value| value.as_bool())
+    .unwrap_or_default()
+}
+
+pub fn radio(name: &str, options: &[&str]) -> String {
+  widget(
+    name,
+    Widget::Radio {
+      options: options.iter().map(|option| option.to_string()).collect(),
+    },
+  )
+  .and_then(|value| value.as_str().map(str::to_owned))
+  .or_else(|| options.first().map(|option| option.to_string()))
+  .unwrap_or_default()
+}
+
+pub fn slider(name: &str, min: f64, max: f64, step: f64, initial: f64) -> f64 {
+  widget(
+    name,
+    Widget::Slider {
+      initial,
+      max,
+      min,
+      step,
+    },
+  )
+  .and_then(|value| value.as_f64())
+  .unwrap_or(initial)
+}
+
+fn widget(name: &str, widget: Widget) -> Option<serde_json::Value> {
+  SYSTEM.with(|system| {
+    let system = system.borrow();
+    let key = widget.key(name);
+    system.send(Message::Widget {
+      name: name.into(),
+      widget,
+    });
+    system.widgets.get(&key).cloned()
+  })
+}
+
 pub fn error(message: impl ToString) {
   SYSTEM.with(|system| {
     system
@@ -33,12 +178,259 @@ pub fn error(message: impl ToString) {
   });
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Automaton {
+  pub channel: Channel,
+  pub rule: String,
+  pub threshold: f32,
+}
+
+impl Automaton {
+  pub fn new(rule: impl Into<String>) -> Self {
+    Self {
+      channel: Channel::Luminance,
+      rule: rule.into(),
+      threshold: 0.5,
+    }
+  }
+
+  pub fn life() -> Self {
+    Self::new("B3/S23")
+  }
+
+  pub fn channel(self, channel: Channel) -> Self {
+    Self { channel, ..self }
+  }
+
+  pub fn threshold(self, threshold: f32) -> Self {
+    Self { threshold, ..self }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorOp {
+  Contrast(f32),
+  HueRotate(f32),
+  InvertLuminance,
+  Saturation(f32),
+  Value(f32),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Curve(pub Vec<[f32; 2]>);
+
+impl Curve {
+  pub fn identity() -> Self {
+    Self(vec![[0.0, 0.0], [1.0, 1.0]])
+  }
+
+  pub fn sample(&self, x: f32) -> f32 {
+    let mut points = self.0.clone();
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    points.dedup_by(|a, b| a[0] == b[0]);
+
+    let n = points.len();
+
+    match points.as_slice() {
+      [] => return x,
+      [[_, y]] => return *y,
+      [first, .., last] => {
+        if x <= first[0] {
+          return first[1];
+        }
+        if x >= last[0] {
+          return last[1];
+        }
+      }
+    }
+
+    let secants = points
+      .windows(2)
+      .map(|w| (w[1][1] - w[0][1]) / (w[1][0] - w[0][0]))
+      .collect::<Vec<f32>>();
+
+    let mut tangents = vec![0.0; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for k in 1..n - 1 {
+      if secants[k - 1] * secants[k] > 0.0 {
+        tangents[k] = (secants[k - 1] + secants[k]) / 2.0;
+      }
+    }
+
+    for k in 0..n - 1 {
+      if secants[k] == 0.0 {
+        tangents[k] = 0.0;
+        tangents[k + 1] = 0.0;
+      } else {
+        let a = tangents[k] / secants[k];
+        let b = tangents[k + 1] / secants[k];
+        let magnitude = a * a + b * b;
+        if magnitude > 9.0 {
+          let t = 3.0 / magnitude.sqrt();
+          tangents[k] = t * a * secants[k];
+          tangents[k + 1] = t * b * secants[k];
+        }
+      }
+    }
+
+    let k = points
+      .iter()
+      .position(|point| point[0] > x)
+      .unwrap_or(n - 1)
+      - 1;
+    let ([x0, y0], [x1, y1]) = (points[k], points[k + 1]);
+    let h = x1 - x0;
+    let t = (x - x0) / h;
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+      + (t3 - 2.0 * t2 + t) * h * tangents[k]
+      + (-2.0 * t3 + 3.0 * t2) * y1
+      + (t3 - t2) * h * tangents[k + 1]
+  }
+}
+
+impl Default for Curve {
+  fn default() -> Self {
+    Self::identity()
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Curves {
+  pub blue: Curve,
+  pub green: Curve,
+  pub luminance: Curve,
+  pub red: Curve,
+}
+
+impl Curves {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn blue(self, blue: Curve) -> Self {
+    Self { blue, ..self }
+  }
+
+  pub fn green(self, green: Curve) -> Self {
+    Self { green, ..self }
+  }
+
+  pub fn luminance(self, luminance: Curve) -> Self {
+    Self { luminance, ..self }
+  }
+
+  pub fn red(self, red: Curve) -> Self {
+    Self { red, ..self }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Direction {
+  Down,
+  Left,
+  Right,
+  Up,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum BlendMode {
+  Add,
+  Difference,
+  Max,
+  Min,
+  Multiply,
+  Normal,
+  Overlay,
+  Screen,
+  Xor,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Channel {
+  Blue,
+  Green,
+  Luminance,
+  Red,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Dither {
+  Bayer,
+  BlueNoise,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum Effect {
+  Bloom {
+    intensity: f32,
+    radius: f32,
+    threshold: f32,
+  },
+  ChromaticAberration(f32),
+  Gamma(f32),
+  Scanlines {
+    intensity: f32,
+    spacing: f32,
+  },
+  Vignette(f32),
+}
+
+impl Effect {
+  // Check that `effects` can be applied. Effects are applied in a fixed
+  // order, so each kind of effect may be given at most once.
+  pub fn validate(effects: &[Effect]) -> Result<(), String> {
+    for (i, effect) in effects.iter().enumerate() {
+      if effects[..i]
+        .iter()
+        .any(|other| mem::discriminant(other) == mem::discriminant(effect))
+      {
+        return Err(format!("Effect `{}` given more than once", effect.name()));
+      }
+
+      if let Effect::Gamma(gamma) = *effect {
+        if gamma <= 0.0 || gamma.is_nan() {
+          return Err(format!("Gamma must be greater than zero, not {gamma}"));
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  fn name(&self) -> &'static str {
+    match self {
+      Self::Bloom { .. } => "bloom",
+      Self::ChromaticAberration(_) => "chromaticAberration",
+      Self::Gamma(_) => "gamma",
+      Self::Scanlines { .. } => "scanlines",
+      Self::Vignette(_) => "vignette",
+    }
+  }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "tag", content = "content")]
 pub enum Event {
   Beat,
   Frame(f32),
+  Key(String),
+  Resize {
+    width: u32,
+    height: u32,
+  },
   Script(String),
   Widget {
     key: String,
@@ -46,15 +438,28 @@ pub enum Event {
   },
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Filter {
   pub alpha: f32,
+  pub automaton: Option<Automaton>,
+  pub blend_mode: BlendMode,
+  pub color_ops: Vec<ColorOp>,
   pub color_transform: Matrix4,
   pub position_transform: Matrix3,
   pub coordinates: bool,
+  pub curves: Option<Curves>,
   pub default_color: [f32; 3],
+  pub delay: u32,
+  pub dither: Option<Dither>,
   pub field: Field,
+  pub gradient_map: Option<Channel>,
+  pub kernel: Option<Kernel>,
+  pub linear: bool,
+  pub posterize: Option<[u32; 3]>,
+  pub reaction_diffusion: Option<ReactionDiffusion>,
+  pub sort: Option<Sort>,
+  pub threshold: Option<f32>,
   pub times: u32,
   pub wrap: bool,
 }
@@ -64,6 +469,41 @@ impl Filter {
     Self::default()
   }
 
+  pub fn all(self) -> Self {
+    Self {
+      field: Field::All,
+      ..self
+    }
+  }
+
+  pub fn modulo(self, divisor: u32, remainder: u32) -> Self {
+    Self {
+      field: Field::Mod { divisor, remainder },
+      ..self
+    }
+  }
+
+  pub fn rows(self, on: u32, off: u32) -> Self {
+    Self {
+      field: Field::Rows { on, off },
+      ..self
+    }
+  }
+
+  pub fn time_domain(self) -> Self {
+    Self {
+      field: Field::TimeDomain,
+      ..self
+    }
+  }
+
+  pub fn wave(self) -> Self {
+    Self {
+      field: Field::Wave,
+      ..self
+    }
+  }
+
   pub fn equalizer(self) -> Self {
     Self {
       field: Field::Equalizer,
@@ -127,6 +567,22 @@ impl Filter {
     }
   }
 
+  pub fn rotate(self, rotation: f32) -> Self {
+    self.transform(rotation, [1.0, 1.0], [0.0, 0.0])
+  }
+
+  pub fn scale(self, scale: f32) -> Self {
+    self.transform(0.0, [scale, scale], [0.0, 0.0])
+  }
+
+  pub fn transform(self, rotation: f32, scale: [f32; 2], translation: [f32; 2]) -> Self {
+    self.position(
+      Rotation2::new(rotation).to_homogeneous()
+        * Matrix3::new_nonuniform_scaling(&Vector2::from(scale))
+        * Matrix3::new_translation(&Vector2::from(translation)),
+    )
+  }
+
   pub fn color(self, color_transform: impl Into<Matrix4>) -> Self {
     Self {
       color_transform: color_transform.into(),
@@ -134,10 +590,37 @@ impl Filter {
     }
   }
 
+  pub fn identity(self) -> Self {
+    self.color(ColorTransform::identity())
+  }
+
+  pub fn invert(self) -> Self {
+    self.color(ColorTransform::invert())
+  }
+
+  pub fn color_op(mut self, color_op: ColorOp) -> Self {
+    self.color_ops.push(color_op);
+    self
+  }
+
   pub fn alpha(self, alpha: f32) -> Self {
     Self { alpha, ..self }
   }
 
+  pub fn coordinates(self, coordinates: bool) -> Self {
+    Self {
+      coordinates,
+      ..self
+    }
+  }
+
+  pub fn default_color(self, default_color: [f32; 3]) -> Self {
+    Self {
+      default_color,
+      ..self
+    }
+  }
+
   pub fn wrap(self, wrap: bool) -> Self {
     Self { wrap, ..self }
   }
@@ -146,8 +629,89 @@ impl Filter {
     Self { times, ..self }
   }
 
+  pub fn delay(self, delay: u32) -> Self {
+    Self { delay, ..self }
+  }
+
+  pub fn automaton(self, automaton: Automaton) -> Self {
+    Self {
+      automaton: Some(automaton),
+      ..self
+    }
+  }
+
+  pub fn blend_mode(self, blend_mode: BlendMode) -> Self {
+    Self { blend_mode, ..self }
+  }
+
+  pub fn curves(self, curves: Curves) -> Self {
+    Self {
+      curves: Some(curves),
+      ..self
+    }
+  }
+
+  pub fn dither(self, dither: Dither) -> Self {
+    Self {
+      dither: Some(dither),
+      ..self
+    }
+  }
+
+  pub fn posterize(self, levels: [u32; 3]) -> Self {
+    Self {
+      posterize: Some(levels),
+      ..self
+    }
+  }
+
+  pub fn threshold(self, threshold: f32) -> Self {
+    Self {
+      threshold: Some(threshold),
+      ..self
+    }
+  }
+
+  pub fn gradient_map(self, channel: Channel) -> Self {
+    Self {
+      gradient_map: Some(channel),
+      ..self
+    }
+  }
+
+  pub fn kernel(self, kernel: Kernel) -> Self {
+    Self {
+      kernel: Some(kernel),
+      ..self
+    }
+  }
+
+  pub fn linear(self, linear: bool) -> Self {
+    Self { linear, ..self }
+  }
+
+  pub fn reaction_diffusion(self, reaction_diffusion: ReactionDiffusion) -> Self {
+    Self {
+      reaction_diffusion: Some(reaction_diffusion),
+      ..self
+    }
+  }
+
+  pub fn sort(self, direction: Direction, threshold: f32) -> Self {
+    Self {
+      sort: Some(Sort {
+        direction,
+        threshold,
+      }),
+      ..self
+    }
+  }
+
   pub fn render(self) -> Self {
-    SYSTEM.with(|system| system.borrow_mut().send(Message::Render(self.clone())));
+    send(Message::Render(Box::new(Filter {
+      alpha: self.alpha * ALPHA.with(Cell::get),
+      ..self.clone()
+    })));
     self
   }
 }
@@ -156,18 +720,31 @@ impl Default for Filter {
   fn default() -> Self {
     Self {
       alpha: 1.0,
-      color_transform: Similarity3::from_scaling(-1.0).into(),
+      automaton: None,
+      blend_mode: BlendMode::Normal,
+      color_ops: Vec::new(),
+      color_transform: ColorTransform::invert().into(),
       position_transform: Matrix3::identity(),
       coordinates: false,
+      curves: None,
       default_color: [0.0, 0.0, 0.0],
+      delay: 0,
+      dither: None,
       field: Field::All,
+      gradient_map: None,
+      kernel: None,
+      linear: false,
+      posterize: None,
+      reaction_diffusion: None,
+      sort: None,
+      threshold: None,
       times: 1,
       wrap: false,
     }
   }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum Field {
   All,
   Check,
@@ -184,6 +761,161 @@ pub enum Field {
   X,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Precision {
+  Byte,
+  Float,
+  Half,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum Scaling {
+  Fill,
+  Fit,
+  Integer,
+  Stretch,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Kernel {
+  Blur,
+  Custom(Vec<f32>),
+  EdgeDetect,
+  Emboss,
+  Sharpen,
+}
+
+impl Kernel {
+  pub fn weights(&self) -> &[f32] {
+    match self {
+      Self::Blur => &[
+        1.0 / 16.0,
+        2.0 / 16.0,
+        1.0 / 16.0,
+        2.0 / 16.0,
+        4.0 / 16.0,
+        2.0 / 16.0,
+        1.0 / 16.0,
+        2.0 / 16.0,
+        1.0 / 16.0,
+      ],
+      Self::Custom(weights) => weights,
+      Self::EdgeDetect => &[-1.0, -1.0, -1.0, -1.0, 8.0, -1.0, -1.0, -1.0, -1.0],
+      Self::Emboss => &[-2.0, -1.0, 0.0, -1.0, 1.0, 1.0, 0.0, 1.0, 2.0],
+      Self::Sharpen => &[0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0],
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Palette {
+  Colors(Vec<[f32; 3]>),
+  Stops(Vec<(f32, [f32; 3])>),
+}
+
+impl Palette {
+  pub fn grayscale() -> Self {
+    Self::Colors(vec![[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]])
+  }
+
+  pub fn stops(&self) -> Vec<(f32, [f32; 3])> {
+    match self {
+      Self::Colors(colors) => colors
+        .iter()
+        .enumerate()
+        .map(|(i, color)| {
+          (
+            i as f32 / colors.len().saturating_sub(1).max(1) as f32,
+            *color,
+          )
+        })
+        .collect(),
+      Self::Stops(stops) => {
+        let mut stops = stops.clone();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        stops
+      }
+    }
+  }
+
+  pub fn sample(&self, position: f32) -> Option<[f32; 3]> {
+    let stops = self.stops();
+
+    let (first, last) = (stops.first()?, stops.last()?);
+
+    if position <= first.0 {
+      return Some(first.1);
+    }
+
+    if position >= last.0 {
+      return Some(last.1);
+    }
+
+    let i = stops.iter().position(|stop| stop.0 > position)?;
+    let (start, end) = (stops[i - 1], stops[i]);
+    let t = (position - start.0) / (end.0 - start.0);
+
+    Some([
+      start.1[0] + (end.1[0] - start.1[0]) * t,
+      start.1[1] + (end.1[1] - start.1[1]) * t,
+      start.1[2] + (end.1[2] - start.1[2]) * t,
+    ])
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactionDiffusion {
+  pub diffusion_a: f32,
+  pub diffusion_b: f32,
+  pub feed: f32,
+  pub kill: f32,
+}
+
+impl ReactionDiffusion {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn diffusion(self, diffusion_a: f32, diffusion_b: f32) -> Self {
+    Self {
+      diffusion_a,
+      diffusion_b,
+      ..self
+    }
+  }
+
+  pub fn feed(self, feed: f32) -> Self {
+    Self { feed, ..self }
+  }
+
+  pub fn kill(self, kill: f32) -> Self {
+    Self { kill, ..self }
+  }
+}
+
+impl Default for ReactionDiffusion {
+  fn default() -> Self {
+    Self {
+      diffusion_a: 1.0,
+      diffusion_b: 0.5,
+      feed: 0.055,
+      kill: 0.062,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Sort {
+  pub direction: Direction,
+  pub threshold: f32,
+}
+
 #[derive(Default, Copy, Clone)]
 pub struct Frame {
   pub delta: f32,
@@ -195,6 +927,7 @@ pub struct Frame {
 pub struct System {
   scope: DedicatedWorkerGlobalScope,
   listener: Option<Closure<dyn FnMut(MessageEvent)>>,
+  widgets: BTreeMap<String, serde_json::Value>,
 }
 
 impl System {
@@ -202,38 +935,46 @@ impl System {
     Self {
       scope: js_sys::global().dyn_into().unwrap(),
       listener: None,
+      widgets: BTreeMap::new(),
     }
   }
 
-  fn execute_inner(&mut self, mut process: Box<dyn Process + 'static>) {
-    let mut frame = Frame::default();
-
-    if let Some(listener) = &self.listener {
-      self
-        .scope
-        .remove_event_listener_with_callback("message", listener.as_ref().dyn_ref().unwrap())
-        .unwrap()
+  fn listen(&mut self) {
+    if self.listener.is_some() {
+      return;
     }
 
-    self.listener = None;
-
     let closure = Closure::wrap(Box::new(move |e: MessageEvent| {
       let event = serde_json::from_str(&e.data().as_string().unwrap()).unwrap();
 
       match event {
-        Event::Frame(time) => {
+        Event::Frame(time) => Processes::dispatch(|frame, process| {
           frame.delta = time - frame.time;
           frame.time = time;
           if process.clear() {
-            SYSTEM.with(|system| system.borrow_mut().send(Message::Clear));
+            clear();
           }
-          process.frame(frame);
+          process.frame(*frame);
           frame.number += 1;
-        }
-        Event::Beat => {
+        }),
+        Event::Beat => Processes::dispatch(|frame, process| {
           frame.beat += 1;
+          process.on_beat(frame.beat);
+        }),
+        Event::Key(key) => Processes::dispatch(|_, process| process.on_key(&key)),
+        Event::Resize { width, height } => {
+          Processes::dispatch(|_, process| process.on_resize(width, height))
+        }
+        Event::Widget { key, value } => {
+          SYSTEM.with(|system| {
+            system
+              .borrow_mut()
+              .widgets
+              .insert(key.clone(), value.clone())
+          });
+          Processes::dispatch(|_, process| process.on_widget(&key, &value));
         }
-        _ => {}
+        Event::Script(_) => {}
       }
     }) as Box<dyn FnMut(MessageEvent)>);
 
@@ -255,6 +996,86 @@ impl System {
   }
 }
 
+// The running process, and the process that will replace it. A process that
+// executes another process from one of its own hooks is borrowed until the
+// hook returns, so the switch is deferred until then.
+#[derive(Default)]
+struct Processes {
+  frame: Frame,
+  pending: Option<Box<dyn Process>>,
+  running: Option<Rc<RefCell<Box<dyn Process>>>>,
+}
+
+impl Processes {
+  fn execute(process: Box<dyn Process>) {
+    let busy = PROCESSES.with(|processes| {
+      processes
+        .borrow()
+        .running
+        .as_ref()
+        .is_some_and(|running| running.try_borrow_mut().is_err())
+    });
+
+    if busy {
+      PROCESSES.with(|processes| processes.borrow_mut().pending = Some(process));
+    } else {
+      Self::switch(process);
+    }
+  }
+
+  fn switch(process: Box<dyn Process>) {
+    let process = Rc::new(RefCell::new(process));
+
+    let previous = PROCESSES.with(|processes| {
+      let mut processes = processes.borrow_mut();
+      processes.frame = Frame::default();
+      processes.running.replace(process.clone())
+    });
+
+    if let Some(previous) = previous {
+      previous.borrow_mut().on_stop();
+    }
+
+    process.borrow_mut().init();
+
+    Self::switch_pending();
+  }
+
+  fn switch_pending() {
+    if let Some(pending) = PROCESSES.with(|processes| processes.borrow_mut().pending.take()) {
+      Self::switch(pending);
+    }
+  }
+
+  // Call `hook` with the running process, then switch to the process it
+  // executed, if any
+  fn dispatch(hook: impl FnOnce(&mut Frame, &mut dyn Process)) {
+    let (mut frame, running) = PROCESSES.with(|processes| {
+      let processes = processes.borrow();
+      (processes.frame, processes.running.clone())
+    });
+
+    let Some(running) = running else {
+      return;
+    };
+
+    hook(&mut frame, &mut **running.borrow_mut());
+
+    PROCESSES.with(|processes| {
+      let mut processes = processes.borrow_mut();
+      if processes
+        .running
+        .as_ref()
+        .is_some_and(|current| Rc::ptr_eq(current, &running))
+      {
+        processes.frame = frame;
+      }
+    });
+
+    Self::switch_pending();
+  }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Widget {
@@ -294,13 +1115,24 @@ pub enum Message {
   Clear,
   DecibelRange { min: f32, max: f32 },
   Done,
+  Effects(Vec<Effect>),
   Error(String),
   OscillatorFrequency(f32),
   OscillatorGain(f32),
+  Palette(Palette),
+  Precision(Precision),
   Record,
-  Render(Filter),
+  Render(Box<Filter>),
   Resolution(u32),
+  RestoreRewind,
+  Rewind(u32),
+  RewindCapacity(u32),
   Save,
+  SaveTiled(u32),
+  Scaling(Scaling),
+  Snapshot,
+  StepBack,
+  StepForward,
   Widget { name: String, widget: Widget },
 }
 
@@ -311,11 +1143,57 @@ pub trait Process {
 
   fn frame(&mut self, frame: Frame);
 
+  fn init(&mut self) {}
+
+  fn on_beat(&mut self, _beat: u64) {}
+
+  fn on_key(&mut self, _key: &str) {}
+
+  fn on_resize(&mut self, _width: u32, _height: u32) {}
+
+  fn on_stop(&mut self) {}
+
+  fn on_widget(&mut self, _key: &str, _value: &serde_json::Value) {}
+
+  fn then<P: Process>(self, duration: f32, next: P) -> Sequence<Self, P>
+  where
+    Self: Sized,
+  {
+    Sequence {
+      first: self,
+      second: next,
+      duration,
+      start: None,
+      switched: false,
+    }
+  }
+
+  fn layer<P: Process>(self, top: P) -> Layer<Self, P>
+  where
+    Self: Sized,
+  {
+    Layer { bottom: self, top }
+  }
+
+  fn crossfade<P: Process>(self, duration: f32, to: P) -> Crossfade<Self, P>
+  where
+    Self: Sized,
+  {
+    Crossfade {
+      from: self,
+      to,
+      duration,
+      progress: 0.0,
+      start: None,
+    }
+  }
+
   fn execute(self)
   where
     Self: Sized + 'static,
   {
-    SYSTEM.with(|system| system.borrow_mut().execute_inner(Box::new(self)));
+    SYSTEM.with(|system| system.borrow_mut().listen());
+    Processes::execute(Box::new(self));
   }
 }
 
@@ -327,3 +1205,148 @@ where
     self(frame);
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use {super::*, approx::assert_abs_diff_eq};
+
+  #[test]
+  fn effects_are_validated() {
+    assert!(Effect::validate(&[Effect::Gamma(2.0), Effect::Vignette(0.5)]).is_ok());
+    assert!(Effect::validate(&[Effect::Gamma(2.0), Effect::Gamma(0.5)]).is_err());
+    assert!(Effect::validate(&[Effect::Gamma(0.0)]).is_err());
+    assert!(Effect::validate(&[Effect::Gamma(f32::NAN)]).is_err());
+  }
+
+  #[test]
+  fn curve_passes_through_points() {
+    let curve = Curve(vec![[0.0, 0.0], [0.25, 0.5], [1.0, 1.0]]);
+    for [x, y] in curve.0.clone() {
+      assert_abs_diff_eq!(curve.sample(x), y);
+    }
+  }
+
+  #[test]
+  fn curve_is_clamped_outside_points() {
+    let curve = Curve(vec![[0.25, 0.75], [0.75, 0.25]]);
+    assert_abs_diff_eq!(curve.sample(0.0), 0.75);
+    assert_abs_diff_eq!(curve.sample(1.0), 0.25);
+  }
+
+  #[test]
+  fn curve_with_two_points_is_linear() {
+    let curve = Curve(vec![[1.0, 0.0], [0.0, 1.0]]);
+    for x in [0.1, 0.25, 0.5, 0.9] {
+      assert_abs_diff_eq!(curve.sample(x), 1.0 - x, epsilon = 1e-6);
+    }
+  }
+
+  #[test]
+  fn curve_is_monotone() {
+    let curve = Curve(vec![[0.0, 0.0], [0.1, 0.8], [0.2, 0.8], [1.0, 1.0]]);
+    let mut previous = 0.0;
+    for i in 0..=100 {
+      let y = curve.sample(i as f32 / 100.0);
+      assert!(y >= previous - 1e-6, "{y} < {previous}");
+      assert!(y <= 1.0 + 1e-6, "{y} > 1");
+      previous = y;
+    }
+  }
+
+  #[test]
+  fn degenerate_curves() {
+    assert_abs_diff_eq!(Curve(Vec::new()).sample(0.3), 0.3);
+    assert_abs_diff_eq!(Curve(vec![[0.5, 0.2]]).sample(0.9), 0.2);
+  }
+
+  #[test]
+  fn palette_colors_are_evenly_spaced() {
+    let palette = Palette::Colors(vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+    assert_eq!(palette.sample(0.0), Some([1.0, 0.0, 0.0]));
+    assert_eq!(palette.sample(0.5), Some([0.0, 1.0, 0.0]));
+    assert_eq!(palette.sample(1.0), Some([0.0, 0.0, 1.0]));
+
+    let [r, g, b] = palette.sample(0.25).unwrap();
+    assert_abs_diff_eq!(r, 0.5);
+    assert_abs_diff_eq!(g, 0.5);
+    assert_abs_diff_eq!(b, 0.0);
+  }
+
+  #[test]
+  fn palette_stops_are_sorted() {
+    let palette = Palette::Stops(vec![(0.75, [1.0, 1.0, 1.0]), (0.25, [0.0, 0.0, 0.0])]);
+    assert_eq!(palette.sample(0.0), Some([0.0, 0.0, 0.0]));
+    assert_eq!(palette.sample(1.0), Some([1.0, 1.0, 1.0]));
+
+    let [r, g, b] = palette.sample(0.375).unwrap();
+    assert_abs_diff_eq!(r, 0.25);
+    assert_abs_diff_eq!(g, 0.25);
+    assert_abs_diff_eq!(b, 0.25);
+  }
+
+  #[test]
+  fn palette_with_one_color_is_constant() {
+    let palette = Palette::Colors(vec![[0.5, 0.5, 0.5]]);
+    assert_eq!(palette.sample(0.0), Some([0.5, 0.5, 0.5]));
+    assert_eq!(palette.sample(1.0), Some([0.5, 0.5, 0.5]));
+  }
+
+  #[test]
+  fn empty_palette() {
+    assert_eq!(Palette::Colors(Vec::new()).sample(0.5), None);
+  }
+
+  struct Recorder {
+    name: &'static str,
+    log: Rc<RefCell<Vec<String>>>,
+    next: Option<Box<dyn Process>>,
+  }
+
+  impl Process for Recorder {
+    fn frame(&mut self, frame: Frame) {
+      self
+        .log
+        .borrow_mut()
+        .push(format!("{} frame {}", self.name, frame.number));
+
+      if let Some(next) = self.next.take() {
+        Processes::execute(next);
+      }
+    }
+
+    fn init(&mut self) {
+      self.log.borrow_mut().push(format!("{} init", self.name));
+    }
+
+    fn on_stop(&mut self) {
+      self.log.borrow_mut().push(format!("{} stop", self.name));
+    }
+  }
+
+  #[test]
+  fn process_replaced_from_hook_is_stopped() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    Processes::execute(Box::new(Recorder {
+      name: "a",
+      log: log.clone(),
+      next: Some(Box::new(Recorder {
+        name: "b",
+        log: log.clone(),
+        next: None,
+      })),
+    }));
+
+    for _ in 0..2 {
+      Processes::dispatch(|frame, process| {
+        process.frame(*frame);
+        frame.number += 1;
+      });
+    }
+
+    assert_eq!(
+      *log.borrow(),
+      ["a init", "a frame 0", "a stop", "b init", "b frame 0"],
+    );
+  }
+}
@@ -1,16 +1,20 @@
 use {
   crate::{
-    add_event_listener::AddEventListener, app::App, cast::Cast, error::Error,
-    get_document::GetDocument, gpu::Gpu, select::Select, stderr::Stderr, window::window,
+    add_event_listener::AddEventListener, app::App, blue_noise::blue_noise, cast::Cast,
+    error::Error, get_document::GetDocument, gpu::Gpu, rule::Rule, select::Select, stderr::Stderr,
+    uniform::Uniform, uniforms::Uniforms, window::window,
+  },
+  degenerate::{
+    BlendMode, Channel, ColorOp, Curves, Direction, Dither, Effect, Event, Field, Filter, Message,
+    Palette, Precision, ReactionDiffusion, Scaling, Widget,
   },
-  degenerate::{Event, Field, Filter, Message, Widget},
   hex::FromHexError,
   image::{ImageBuffer, ImageError, ImageOutputFormat},
-  js_sys::{Float32Array, Promise},
+  js_sys::{Array, Float32Array, Promise, Uint8Array},
   lazy_static::lazy_static,
-  nalgebra::{Matrix3, Matrix4},
+  nalgebra::{Matrix3, Matrix4, Vector3},
   std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, VecDeque},
     convert::Infallible,
     f32,
     fmt::{self, Display, Formatter},
@@ -19,18 +23,18 @@ use {
     num::TryFromIntError,
     ops::Deref,
     rc::Rc,
-    str::{self, Utf8Error},
+    str::{self, FromStr, Utf8Error},
     string::ToString,
     sync::Mutex,
   },
   wasm_bindgen::{closure::Closure, convert::FromWasmAbi, JsCast, JsValue},
   web_sys::{
-    AnalyserNode, AudioContext, Document, EventTarget, GainNode, HtmlAnchorElement,
-    HtmlButtonElement, HtmlCanvasElement, HtmlDivElement, HtmlElement, HtmlInputElement,
-    HtmlLabelElement, HtmlOptionElement, HtmlSelectElement, HtmlSpanElement, HtmlTextAreaElement,
-    KeyboardEvent, MediaStream, MediaStreamConstraints, MessageEvent, OscillatorNode,
-    WebGl2RenderingContext, WebGlContextAttributes, WebGlFramebuffer, WebGlTexture,
-    WebGlUniformLocation, Window, Worker, WorkerOptions, WorkerType,
+    AnalyserNode, AudioContext, Blob, BlobPropertyBag, Document, EventTarget, GainNode,
+    HtmlAnchorElement, HtmlButtonElement, HtmlCanvasElement, HtmlDivElement, HtmlElement,
+    HtmlInputElement, HtmlLabelElement, HtmlOptionElement, HtmlSelectElement, HtmlSpanElement,
+    HtmlTextAreaElement, KeyboardEvent, MediaStream, MediaStreamConstraints, MessageEvent,
+    OscillatorNode, Url, WebGl2RenderingContext, WebGlContextAttributes, WebGlFramebuffer,
+    WebGlProgram, WebGlTexture, WebGlUniformLocation, Window, Worker, WorkerOptions, WorkerType,
   },
 };
 
@@ -38,12 +42,16 @@ type Result<T = (), E = Error> = std::result::Result<T, E>;
 
 mod add_event_listener;
 mod app;
+mod blue_noise;
 mod cast;
 mod error;
 mod get_document;
 mod gpu;
+mod rule;
 mod select;
 mod stderr;
+mod uniform;
+mod uniforms;
 mod window;
 
 fn main() {
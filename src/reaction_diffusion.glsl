@@ -0,0 +1,51 @@
+#version 300 es
+
+precision highp float;
+
+const vec3 LUMINANCE = vec3(0.2126, 0.7152, 0.0722);
+
+uniform bool seed;
+uniform float diffusion_a;
+uniform float diffusion_b;
+uniform float feed;
+uniform float kill;
+uniform float resolution;
+uniform sampler2D state;
+
+out vec4 output_color;
+
+void main() {
+  vec2 coordinates = gl_FragCoord.xy / resolution;
+
+  // Initialize chemical A to 1 everywhere, and chemical B to 1 where the
+  // canvas is bright
+  if (seed) {
+    float b = dot(texture(state, coordinates).rgb, LUMINANCE) > 0.5 ? 1.0 : 0.0;
+    output_color = vec4(1.0, b, 0.0, 1.0);
+    return;
+  }
+
+  // Sample chemical concentrations, which are stored in the red and green
+  // channels
+  vec2 concentration = texture(state, coordinates).rg;
+
+  // Calculate the laplacian using a 3×3 kernel
+  vec2 laplacian = -concentration;
+  for (int x = -1; x <= 1; x++) {
+    for (int y = -1; y <= 1; y++) {
+      if (x != 0 || y != 0) {
+        float weight = x == 0 || y == 0 ? 0.2 : 0.05;
+        laplacian += texture(state, coordinates + vec2(x, y) / resolution).rg * weight;
+      }
+    }
+  }
+
+  // Apply the Gray-Scott equations
+  float a = concentration.r;
+  float b = concentration.g;
+  float reaction = a * b * b;
+  a += diffusion_a * laplacian.r - reaction + feed * (1.0 - a);
+  b += diffusion_b * laplacian.g + reaction - (kill + feed) * b;
+
+  output_color = vec4(clamp(a, 0.0, 1.0), clamp(b, 0.0, 1.0), 0.0, 1.0);
+}
@@ -0,0 +1,81 @@
+use super::*;
+
+// A cellular automaton rule in B/S notation, for example `B3/S23` for
+// Conway's Game of Life. Bit `n` of `birth` is set if dead cells with `n` live
+// neighbors are born, and bit `n` of `survival` is set if live cells with `n`
+// live neighbors survive.
+pub(crate) struct Rule {
+  pub(crate) birth: u32,
+  pub(crate) survival: u32,
+}
+
+impl FromStr for Rule {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self> {
+    let mut birth = None;
+    let mut survival = None;
+
+    for part in s.split('/') {
+      let mut chars = part.chars();
+
+      let mask = match chars.next().map(|c| c.to_ascii_uppercase()) {
+        Some('B') => &mut birth,
+        Some('S') => &mut survival,
+        _ => return Err(format!("Invalid rule `{s}`: expected `B` or `S` in `{part}`").into()),
+      };
+
+      let mut bits = 0;
+
+      for c in chars {
+        let neighbors = c
+          .to_digit(10)
+          .filter(|neighbors| *neighbors <= 8)
+          .ok_or_else(|| format!("Invalid rule `{s}`: `{c}` is not a neighbor count"))?;
+        bits |= 1 << neighbors;
+      }
+
+      if mask.replace(bits).is_some() {
+        return Err(format!("Invalid rule `{s}`: `{part}` given twice").into());
+      }
+    }
+
+    Ok(Self {
+      birth: birth.ok_or_else(|| format!("Invalid rule `{s}`: missing `B`"))?,
+      survival: survival.ok_or_else(|| format!("Invalid rule `{s}`: missing `S`"))?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn life() {
+    let rule = "B3/S23".parse::<Rule>().unwrap();
+    assert_eq!(rule.birth, 0b1000);
+    assert_eq!(rule.survival, 0b1100);
+  }
+
+  #[test]
+  fn order_and_case_are_ignored() {
+    let rule = "s23/b36".parse::<Rule>().unwrap();
+    assert_eq!(rule.birth, 0b100_1000);
+    assert_eq!(rule.survival, 0b1100);
+  }
+
+  #[test]
+  fn empty_parts() {
+    let rule = "B/S012345678".parse::<Rule>().unwrap();
+    assert_eq!(rule.birth, 0);
+    assert_eq!(rule.survival, 0b1_1111_1111);
+  }
+
+  #[test]
+  fn invalid() {
+    for rule in ["", "B3", "S23", "B3/S23/B3", "X3/S23", "B9/S23", "B3/Sx"] {
+      assert!(rule.parse::<Rule>().is_err(), "{rule}");
+    }
+  }
+}
@@ -0,0 +1,461 @@
+use super::*;
+
+// A show is a list of cues, in order. Each cue starts a scene, identified by
+// name, when its trigger is reached. Scenes are registered with the timeline
+// that plays the show, so shows can be serialized and authored ahead of time.
+//
+// Of the cues whose triggers have been reached, the last one in the show is
+// played. Beats and seconds cannot be compared, so cues with triggers of the
+// same kind must be in order, but beat and second cues may be interleaved.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Show {
+  pub cues: Vec<Cue>,
+}
+
+impl Show {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn cue(mut self, scene: impl Into<String>, at: Trigger, transition: Transition) -> Self {
+    self.cues.push(Cue {
+      at,
+      scene: scene.into(),
+      transition,
+    });
+    self
+  }
+
+  // Check that cues with triggers of the same kind are in order.
+  pub fn validate(&self) -> Result<(), String> {
+    let mut beat = None;
+    let mut seconds = None;
+
+    for cue in &self.cues {
+      let out_of_order = match cue.at {
+        Trigger::Beat(at) => beat.replace(at).is_some_and(|previous| at < previous),
+        Trigger::Seconds(at) => seconds.replace(at).is_some_and(|previous| at < previous),
+      };
+
+      if out_of_order {
+        return Err(format!(
+          "Cue for scene `{}` is out of order, cues must be sorted by trigger",
+          cue.scene
+        ));
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl FromStr for Show {
+  type Err = serde_json::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    serde_json::from_str(s)
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cue {
+  pub at: Trigger,
+  pub scene: String,
+  pub transition: Transition,
+}
+
+// Triggers are measured from the first frame of the timeline.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Trigger {
+  Beat(u64),
+  Seconds(f32),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Transition {
+  Crossfade(f32),
+  Cut,
+}
+
+pub struct Timeline {
+  current: Option<usize>,
+  cue_start: f32,
+  previous: Option<usize>,
+  scenes: BTreeMap<String, Box<dyn Process>>,
+  show: Show,
+  start: Option<(f32, u64)>,
+  transition: Transition,
+}
+
+impl Timeline {
+  pub fn new(show: Show) -> Self {
+    Self {
+      current: None,
+      cue_start: 0.0,
+      previous: None,
+      scenes: BTreeMap::new(),
+      show,
+      start: None,
+      transition: Transition::Cut,
+    }
+  }
+
+  pub fn scene(mut self, name: impl Into<String>, process: impl Process + 'static) -> Self {
+    self.scenes.insert(name.into(), Box::new(process));
+    self
+  }
+
+  fn scene_mut(&mut self, cue: Option<usize>) -> Option<&mut Box<dyn Process>> {
+    let name = &self.show.cues.get(cue?)?.scene;
+    self.scenes.get_mut(name)
+  }
+
+  fn reached(&self, frame: Frame) -> Option<usize> {
+    let (start_time, start_beat) = self.start?;
+    let elapsed = (frame.time - start_time) / 1000.0;
+    let beats = frame.beat - start_beat;
+
+    self.show.cues.iter().rposition(|cue| match cue.at {
+      Trigger::Beat(beat) => beats >= beat,
+      Trigger::Seconds(seconds) => elapsed >= seconds,
+    })
+  }
+
+  // The progress of the transition into the current scene. Cues that continue
+  // the current scene do not restart its transition.
+  fn progress(&self, frame: Frame) -> f32 {
+    match self.transition {
+      Transition::Crossfade(duration) if duration > 0.0 => {
+        ((frame.time - self.cue_start) / (duration * 1000.0)).clamp(0.0, 1.0)
+      }
+      _ => 1.0,
+    }
+  }
+
+  fn stop_previous(&mut self) {
+    if let Some(scene) = self.scene_mut(self.previous) {
+      scene.on_stop();
+    }
+    self.previous = None;
+  }
+
+  fn each(&mut self, mut f: impl FnMut(&mut Box<dyn Process>)) {
+    for cue in [self.previous, self.current] {
+      if let Some(scene) = self.scene_mut(cue) {
+        f(scene);
+      }
+    }
+  }
+}
+
+impl Process for Timeline {
+  fn clear(&self) -> bool {
+    self
+      .previous
+      .or(self.current)
+      .and_then(|cue| self.scenes.get(&self.show.cues[cue].scene))
+      .map(|scene| scene.clear())
+      .unwrap_or(true)
+  }
+
+  fn frame(&mut self, frame: Frame) {
+    self.start.get_or_insert((frame.time, frame.beat));
+
+    let reached = self.reached(frame);
+
+    if reached != self.current {
+      self.stop_previous();
+
+      let scene = |cue: Option<usize>| cue.map(|cue| &self.show.cues[cue].scene);
+
+      if scene(reached) == scene(self.current) {
+        self.current = reached;
+      } else {
+        self.previous = self.current;
+        self.current = reached;
+        self.cue_start = frame.time;
+        self.transition = reached
+          .map(|cue| self.show.cues[cue].transition)
+          .unwrap_or(Transition::Cut);
+
+        if let Some(scene) = self.scene_mut(self.current) {
+          scene.init();
+        }
+      }
+    }
+
+    let progress = self.progress(frame);
+
+    if progress >= 1.0 {
+      self.stop_previous();
+    }
+
+    if let Some(scene) = self.scene_mut(self.previous) {
+      scene.frame(frame);
+    }
+
+    if let Some(scene) = self.scene_mut(self.current) {
+      with_alpha(progress, || scene.frame(frame));
+    }
+  }
+
+  fn init(&mut self) {
+    if let Err(err) = self.show.validate() {
+      error(err);
+    }
+
+    for cue in &self.show.cues {
+      if !self.scenes.contains_key(&cue.scene) {
+        error(format!("Scene `{}` is not registered", cue.scene));
+      }
+    }
+  }
+
+  fn on_beat(&mut self, beat: u64) {
+    self.each(|scene| scene.on_beat(beat));
+  }
+
+  fn on_key(&mut self, key: &str) {
+    self.each(|scene| scene.on_key(key));
+  }
+
+  fn on_resize(&mut self, width: u32, height: u32) {
+    self.each(|scene| scene.on_resize(width, height));
+  }
+
+  fn on_stop(&mut self) {
+    self.each(|scene| scene.on_stop());
+  }
+
+  fn on_widget(&mut self, key: &str, value: &serde_json::Value) {
+    self.each(|scene| scene.on_widget(key, value));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn frame(time: f32, beat: u64) -> Frame {
+    Frame {
+      time,
+      beat,
+      ..Frame::default()
+    }
+  }
+
+  fn timeline() -> Timeline {
+    let mut timeline = Timeline::new(
+      Show::new()
+        .cue("a", Trigger::Seconds(0.0), Transition::Cut)
+        .cue("b", Trigger::Beat(4), Transition::Cut)
+        .cue("c", Trigger::Seconds(10.0), Transition::Crossfade(1.0)),
+    );
+    timeline.start = Some((1000.0, 2));
+    timeline
+  }
+
+  #[test]
+  fn nothing_is_reached_before_start() {
+    let timeline = Timeline::new(Show::new().cue("a", Trigger::Seconds(0.0), Transition::Cut));
+    assert_eq!(timeline.reached(frame(0.0, 0)), None);
+  }
+
+  #[test]
+  fn triggers_are_measured_from_start() {
+    let timeline = timeline();
+    assert_eq!(timeline.reached(frame(1000.0, 2)), Some(0));
+    assert_eq!(timeline.reached(frame(2000.0, 5)), Some(0));
+    assert_eq!(timeline.reached(frame(2000.0, 6)), Some(1));
+    assert_eq!(timeline.reached(frame(10999.0, 6)), Some(1));
+    assert_eq!(timeline.reached(frame(11000.0, 6)), Some(2));
+  }
+
+  #[test]
+  fn latest_reached_cue_wins() {
+    let timeline = timeline();
+    assert_eq!(timeline.reached(frame(11000.0, 2)), Some(2));
+  }
+
+  #[test]
+  fn cues_must_be_in_order() {
+    assert!(Show::new()
+      .cue("a", Trigger::Seconds(1.0), Transition::Cut)
+      .cue("b", Trigger::Beat(8), Transition::Cut)
+      .cue("c", Trigger::Seconds(2.0), Transition::Cut)
+      .cue("d", Trigger::Beat(4), Transition::Cut)
+      .validate()
+      .is_err());
+
+    assert!(Show::new()
+      .cue("a", Trigger::Seconds(1.0), Transition::Cut)
+      .cue("b", Trigger::Beat(8), Transition::Cut)
+      .cue("c", Trigger::Seconds(1.0), Transition::Cut)
+      .cue("d", Trigger::Beat(9), Transition::Cut)
+      .validate()
+      .is_ok());
+  }
+
+  #[test]
+  fn no_cue_reached() {
+    let mut timeline = Timeline::new(Show::new().cue("a", Trigger::Beat(1), Transition::Cut));
+    timeline.start = Some((0.0, 0));
+    assert_eq!(timeline.reached(frame(0.0, 0)), None);
+    assert_eq!(timeline.reached(frame(0.0, 1)), Some(0));
+  }
+
+  type Log = Rc<RefCell<Vec<String>>>;
+
+  struct Scene {
+    log: Log,
+    name: &'static str,
+  }
+
+  impl Process for Scene {
+    fn frame(&mut self, _frame: Frame) {
+      self
+        .log
+        .borrow_mut()
+        .push(format!("{} frame {}", self.name, ALPHA.with(Cell::get)));
+    }
+
+    fn init(&mut self) {
+      self.log.borrow_mut().push(format!("{} init", self.name));
+    }
+
+    fn on_stop(&mut self) {
+      self.log.borrow_mut().push(format!("{} stop", self.name));
+    }
+  }
+
+  fn play(show: Show, frames: &[Frame]) -> Vec<String> {
+    let log = Log::default();
+
+    let mut timeline = Timeline::new(show);
+
+    for name in ["a", "b"] {
+      timeline = timeline.scene(
+        name,
+        Scene {
+          log: log.clone(),
+          name,
+        },
+      );
+    }
+
+    timeline.init();
+
+    for frame in frames {
+      timeline.frame(*frame);
+    }
+
+    timeline.on_stop();
+
+    log.take()
+  }
+
+  #[test]
+  fn cut_switches_scenes_at_cue() {
+    let log = play(
+      Show::new()
+        .cue("a", Trigger::Seconds(0.0), Transition::Cut)
+        .cue("b", Trigger::Beat(2), Transition::Cut),
+      &[
+        frame(0.0, 0),
+        frame(500.0, 1),
+        frame(1000.0, 2),
+        frame(1500.0, 3),
+      ],
+    );
+
+    assert_eq!(
+      log,
+      [
+        "a init",
+        "a frame 1",
+        "a frame 1",
+        "b init",
+        "a stop",
+        "b frame 1",
+        "b frame 1",
+        "b stop",
+      ],
+    );
+  }
+
+  #[test]
+  fn crossfade_ramps_in_next_scene() {
+    let log = play(
+      Show::new()
+        .cue("a", Trigger::Seconds(0.0), Transition::Cut)
+        .cue("b", Trigger::Seconds(1.0), Transition::Crossfade(1.0)),
+      &[
+        frame(0.0, 0),
+        frame(1000.0, 0),
+        frame(1500.0, 0),
+        frame(2000.0, 0),
+        frame(2500.0, 0),
+      ],
+    );
+
+    assert_eq!(
+      log,
+      [
+        "a init",
+        "a frame 1",
+        "b init",
+        "a frame 1",
+        "b frame 0",
+        "a frame 1",
+        "b frame 0.5",
+        "a stop",
+        "b frame 1",
+        "b frame 1",
+        "b stop",
+      ],
+    );
+  }
+
+  #[test]
+  fn crossfade_interrupted_by_cut() {
+    let log = play(
+      Show::new()
+        .cue("a", Trigger::Seconds(0.0), Transition::Cut)
+        .cue("b", Trigger::Seconds(1.0), Transition::Crossfade(1.0))
+        .cue("a", Trigger::Seconds(1.5), Transition::Cut),
+      &[frame(0.0, 0), frame(1000.0, 0), frame(1500.0, 0)],
+    );
+
+    assert_eq!(
+      log,
+      [
+        "a init",
+        "a frame 1",
+        "b init",
+        "a frame 1",
+        "b frame 0",
+        "a stop",
+        "a init",
+        "b stop",
+        "a frame 1",
+        "a stop",
+      ],
+    );
+  }
+
+  #[test]
+  fn consecutive_cues_for_one_scene_continue_it() {
+    let log = play(
+      Show::new()
+        .cue("a", Trigger::Seconds(0.0), Transition::Cut)
+        .cue("a", Trigger::Beat(1), Transition::Crossfade(1.0)),
+      &[frame(0.0, 0), frame(500.0, 1)],
+    );
+
+    assert_eq!(log, ["a init", "a frame 1", "a frame 1", "a stop"]);
+  }
+}
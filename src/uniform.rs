@@ -0,0 +1,14 @@
+use super::*;
+
+#[derive(Clone, PartialEq)]
+pub(crate) enum Uniform {
+  Float(f32),
+  FloatArray(Vec<f32>),
+  Int(i32),
+  IntArray(Vec<i32>),
+  Mat3(Matrix3<f32>),
+  Mat4(Matrix4<f32>),
+  UnsignedInt(u32),
+  Vec2([f32; 2]),
+  Vec3([f32; 3]),
+}
@@ -0,0 +1,64 @@
+use super::*;
+
+pub(crate) struct Uniforms {
+  locations: BTreeMap<String, WebGlUniformLocation>,
+  values: BTreeMap<String, Uniform>,
+}
+
+impl Uniforms {
+  pub(crate) fn new(gl: &WebGl2RenderingContext, program: &WebGlProgram) -> Result<Self> {
+    let uniform_count = gl
+      .get_program_parameter(program, WebGl2RenderingContext::ACTIVE_UNIFORMS)
+      .cast::<js_sys::Number>()?
+      .value_of() as u32;
+
+    let mut locations = BTreeMap::new();
+
+    for i in 0..uniform_count {
+      let info = gl
+        .get_active_uniform(program, i)
+        .ok_or_else(|| format!("Failed to get active uniform {i}"))?;
+      let name = info.name();
+      let location = gl
+        .get_uniform_location(program, &name)
+        .ok_or_else(|| format!("Failed to get location of uniform `{name}`"))?;
+      locations.insert(name, location);
+    }
+
+    Ok(Self {
+      locations,
+      values: BTreeMap::new(),
+    })
+  }
+
+  pub(crate) fn set(&mut self, gl: &WebGl2RenderingContext, name: &str, value: Uniform) -> Result {
+    let location = self
+      .locations
+      .get(name)
+      .ok_or_else(|| format!("Uniform `{name}` is missing."))?;
+
+    if self.values.get(name) == Some(&value) {
+      return Ok(());
+    }
+
+    match &value {
+      Uniform::Float(value) => gl.uniform1f(Some(location), *value),
+      Uniform::FloatArray(values) => gl.uniform1fv_with_f32_array(Some(location), values),
+      Uniform::Int(value) => gl.uniform1i(Some(location), *value),
+      Uniform::IntArray(values) => gl.uniform1iv_with_i32_array(Some(location), values),
+      Uniform::Mat3(value) => {
+        gl.uniform_matrix3fv_with_f32_array(Some(location), false, value.as_slice())
+      }
+      Uniform::Mat4(value) => {
+        gl.uniform_matrix4fv_with_f32_array(Some(location), false, value.as_slice())
+      }
+      Uniform::UnsignedInt(value) => gl.uniform1ui(Some(location), *value),
+      Uniform::Vec2([f0, f1]) => gl.uniform2f(Some(location), *f0, *f1),
+      Uniform::Vec3([f0, f1, f2]) => gl.uniform3f(Some(location), *f0, *f1, *f2),
+    }
+
+    self.values.insert(name.into(), value);
+
+    Ok(())
+  }
+}
@@ -360,3 +360,141 @@ test('js-error-samp', async ({ page }) => {
     'ReferenceError: foo is not defined'
   );
 });
+
+test('save-tiled-matches-save', async ({ page }) => {
+  let downloads = [];
+
+  page.on('download', (download) => downloads.push(download));
+
+  await run(
+    page,
+    `
+      resolution(4096);
+      x();
+      rotate(0.1);
+      render();
+      kernel('blur');
+      render();
+      save();
+      save(4096);
+    `
+  );
+
+  await expect.poll(() => downloads.length).toBe(2);
+
+  let [plain, tiled] = await Promise.all(
+    downloads.map(async (download) =>
+      png.decode(await fs.promises.readFile(await download.path()))
+    )
+  );
+
+  await expect(tiled.width).toBe(plain.width);
+  await expect(tiled.height).toBe(plain.height);
+  await expect(Buffer.compare(tiled.data, plain.data)).toBe(0);
+});
+
+test('save-tiled-rejects-invalid-resolution', async ({ page }) => {
+  try {
+    await run(page, 'save(65536);');
+  } catch {}
+  await expect(await page.locator('samp > *')).toHaveText(
+    'Tiled image resolution must be between 1 and 16384, not 65536'
+  );
+});
+
+test('context-loss', async ({ page }) => {
+  await page.evaluate(() => {
+    let canvas = document.getElementsByTagName('canvas')[0];
+    window['loseContext'] = canvas
+      .getContext('webgl2')
+      .getExtension('WEBGL_lose_context');
+    window['loseContext'].loseContext();
+  });
+
+  await run(page, 'clear(); x(); render(); save(); save(512);');
+
+  await page.evaluate(
+    () =>
+      new Promise((resolve) => {
+        let canvas = document.getElementsByTagName('canvas')[0];
+        canvas.addEventListener('webglcontextrestored', resolve, {
+          once: true,
+        });
+        window['loseContext'].restoreContext();
+      })
+  );
+
+  await run(page, 'x(); render();');
+
+  await expect(
+    Buffer.compare(
+      png.decode(await imageBuffer(page)).data,
+      png.decode(await fs.promises.readFile('../images/x.png')).data
+    )
+  ).toBe(0);
+});
+
+for (const precision of ['half', 'float']) {
+  test(`precision-${precision}-matches-byte`, async ({ page }) => {
+    let script = `
+      x();
+      rotate(0.1);
+      render();
+      all();
+      identity();
+      alpha(0.5);
+      render();
+    `;
+
+    await run(page, script);
+
+    let byte = png.decode(await imageBuffer(page)).data;
+
+    await run(page, `precision('${precision}'); ${script}`);
+
+    let float = png.decode(await imageBuffer(page)).data;
+
+    let difference = 0;
+
+    for (let i = 0; i < byte.length; i++) {
+      difference = Math.max(difference, Math.abs(byte[i] - float[i]));
+    }
+
+    await expect(difference).toBeLessThanOrEqual(1);
+  });
+}
+
+test('precision-without-float-color-buffer', async ({ page }) => {
+  await page.addInitScript(() => {
+    const getExtension = WebGL2RenderingContext.prototype.getExtension;
+    WebGL2RenderingContext.prototype.getExtension = function (name) {
+      return name === 'EXT_color_buffer_float'
+        ? null
+        : getExtension.call(this, name);
+    };
+  });
+
+  await page.reload();
+  await page.evaluate('window.preserveDrawingBuffer = true');
+  await page.waitForSelector('html.ready');
+
+  await run(page, "precision('float'); x(); render();");
+
+  await expect(
+    Buffer.compare(
+      png.decode(await imageBuffer(page)).data,
+      png.decode(await fs.promises.readFile('../images/x.png')).data
+    )
+  ).toBe(0);
+
+  try {
+    await run(
+      page,
+      'x(); render(); all(); identity(); reactionDiffusion(0.055, 0.062, 0, 0); render();'
+    );
+  } catch {}
+
+  await expect(await page.locator('samp > *')).toHaveText(
+    'Reaction diffusion requires the `EXT_color_buffer_float` WebGL extension'
+  );
+});
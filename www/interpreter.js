@@ -50,6 +50,55 @@ function assert(condition, message) {
   }
 }
 
+// Treat the canvas as a cellular automaton, advancing it by one generation
+// each time the filter is applied. Cells are alive if their `channel` is
+// greater than `threshold`. `rule` is given in B/S notation, listing the live
+// neighbor counts that cause dead cells to be born and live cells to survive.
+// Live cells are output as white and dead cells as black, before the color
+// transform is applied. `channel` may be `'red'`, `'green'`, `'blue'`, or
+// `'luminance'`. Pass `null` to disable.
+//
+// ```
+// mod(3, 0);
+// render();
+// identity();
+// all();
+// automaton('B3/S23');
+// while(true) {
+//   await render();
+// }
+// ```
+function automaton(rule, channel, threshold) {
+  if (rule === null) {
+    state.filter.automaton = null;
+  } else {
+    state.filter.automaton = {
+      rule: rule ?? 'B3/S23',
+      channel: channel ?? 'luminance',
+      threshold: threshold ?? 0.5,
+    };
+  }
+}
+
+// Set the blend mode, which controls how the transformed color is combined
+// with the original color, before alpha blending. Valid values for `mode`
+// are `'normal'`, `'add'`, `'difference'`, `'max'`, `'min'`, `'multiply'`,
+// `'overlay'`, `'screen'`, and `'xor'`. The default is `'normal'`, which
+// uses the transformed color as is.
+//
+// ```
+// x();
+// render();
+// identity();
+// rotate(0.1 * TAU);
+// blendMode('difference');
+// all();
+// render();
+// ```
+function blendMode(mode) {
+  state.filter.blendMode = mode ?? 'normal';
+}
+
 // A checkerboard pattern.
 //
 // ```
@@ -127,6 +176,34 @@ function cross() {
   state.filter.field = 'Cross';
 }
 
+// Apply tone curves after the color transform. `curves` is an object with
+// optional `red`, `green`, `blue`, and `luminance` curves, each an array of
+// `[input, output]` control points from 0 to 1, which are joined by a smooth
+// curve. Missing curves are left unchanged. The red, green, and blue curves
+// are applied first, then the luminance curve. Pass `null` to disable.
+//
+// ```
+// identity();
+// curves({
+//   luminance: [[0, 0], [0.25, 0.15], [0.75, 0.85], [1, 1]],
+//   blue: [[0, 0.1], [1, 0.9]],
+// });
+// render();
+// ```
+function curves(curves) {
+  if (curves === null) {
+    state.filter.curves = null;
+  } else {
+    const identity = [[0, 0], [1, 1]];
+    state.filter.curves = {
+      red: curves.red ?? identity,
+      green: curves.green ?? identity,
+      blue: curves.blue ?? identity,
+      luminance: curves.luminance ?? identity,
+    };
+  }
+}
+
 // Set the decibel range for normalization of raw frequency data into values
 // usable in the fragment shader. Frequency data, by default, is expressed in
 // decibels. Decibels are logarithmic, with 0 representing the loudest possible
@@ -170,6 +247,25 @@ function defaultColor(defaultColor) {
   state.filter.defaultColor = defaultColor;
 }
 
+// Sample the canvas as it was `frames` frames ago, instead of the current
+// canvas. The color of the current canvas is still used for alpha blending.
+// Defaults to 0, which samples the current canvas. Past frames are kept on the
+// GPU, so large delays can use a lot of memory. Until enough frames have been
+// rendered, the oldest available frame is used.
+//
+// ```
+// circle();
+// scale(0.95);
+// delay(10);
+// alpha(0.5);
+// while(true) {
+//   await render();
+// }
+// ```
+function delay(frames) {
+  state.filter.delay = frames;
+}
+
 // Return the number of milliseconds that have elapsed between this frame and the last.
 // Returns 0 for the first frame.
 //
@@ -188,6 +284,63 @@ function delta() {
   return state.delta;
 }
 
+// Use ordered dithering when quantizing colors with `posterize` or
+// `threshold`. Valid values for `mode` are `'bayer'`, for an 8×8 Bayer
+// matrix, and `'blueNoise'`, for a blue noise threshold map. Pass `null` to
+// disable.
+//
+// ```
+// identity();
+// posterize(2);
+// dither('bayer');
+// render();
+// ```
+function dither(mode) {
+  state.filter.dither = mode === undefined ? 'bayer' : mode;
+}
+
+// Set display effects. Display effects are applied when the canvas is shown
+// on screen, but never written back to the canvas, so they do not compound as
+// filters are applied. Each kind of effect may be given at most once, and
+// effects are always applied in the following order, regardless of the order
+// they are given in:
+//
+// - `{ chromaticAberration: offset }`: Offset the red and blue channels by up
+//   to `offset` pixels away from the center.
+// - `{ bloom: { threshold, intensity, radius } }`: Blur colors brighter than
+//   `threshold` over `radius` pixels, and add them back scaled by `intensity`.
+// - `{ vignette: strength }`: Darken the edges of the screen.
+// - `{ scanlines: { spacing, intensity } }`: Darken alternating runs of
+//   `spacing` rows by `intensity`.
+// - `{ gamma: gamma }`: Apply gamma correction. `gamma` must be greater than
+//   zero.
+//
+// Call `effects([])` to disable all effects.
+//
+// ```
+// effects([
+//   { bloom: { threshold: 0.5, intensity: 1.0, radius: 8.0 } },
+//   { scanlines: { spacing: 2.0, intensity: 0.25 } },
+//   { vignette: 0.5 },
+// ]);
+// x();
+// render();
+// ```
+function effects(effects) {
+  const names = new Set();
+  for (const effect of effects) {
+    const name = Object.keys(effect)[0];
+    if (names.has(name)) {
+      throw `Effect \`${name}\` given more than once`;
+    }
+    names.add(name);
+    if (name === 'gamma' && !(effect.gamma > 0)) {
+      throw `Gamma must be greater than zero, not ${effect.gamma}`;
+    }
+  }
+  self.postMessage(JSON.stringify({ effects }));
+}
+
 // Return the number of milliseconds that have elapsed since the page was loaded.
 //
 // ```
@@ -241,6 +394,20 @@ function frequency() {
   state.filter.field = 'Frequency';
 }
 
+// Map each pixel's `channel` through the palette, after the color transform.
+// Valid values for `channel` are `'red'`, `'green'`, `'blue'`, and
+// `'luminance'`. Pass `null` to disable. Set the palette with `palette`.
+//
+// ```
+// palette([[0, 0, 0.5], [1, 0.5, 0], [1, 1, 1]]);
+// x();
+// gradientMap('luminance');
+// render();
+// ```
+function gradientMap(channel) {
+  state.filter.gradientMap = channel === undefined ? 'luminance' : channel;
+}
+
 // Set the color transformation to the identity transformation. The identity
 // transformation returns the sampled pixel unchanged. Useful for applying
 // transformations, such as scales or rotation, without changing the sampled
@@ -254,6 +421,29 @@ function identity() {
   mat4.identity(state.filter.colorTransform);
 }
 
+// Set color operations, which are applied in order after the color
+// transform. Each operation is either a name, or an array containing a name
+// and a parameter:
+//
+// - `['contrast', c]`: Scale distance from middle gray by `c`.
+// - `['hueRotate', radians]`: Rotate hue by `radians`.
+// - `'invertLuminance'`: Invert luminance while keeping hue.
+// - `['saturation', s]`: Scale saturation by `s`.
+// - `['value', v]`: Scale value, or brightness, by `v`.
+//
+// At most 8 operations may be given. Call with no arguments to clear.
+//
+// ```
+// identity();
+// colorOps(['hueRotate', 0.1 * TAU], ['saturation', 1.5]);
+// render();
+// ```
+function colorOps(...ops) {
+  state.filter.colorOps = ops.map((op) =>
+    Array.isArray(op) ? { [op[0]]: op[1] } : op
+  );
+}
+
 // If `coordinates` is true, use the coordinate of the sample as the input color,
 // instead of the color of the pixel in the source image. Defaults to false.
 // Useful for creating gradients or debugging coordinate transforms.
@@ -287,6 +477,44 @@ function invert() {
   mat4.fromScaling(state.filter.colorTransform, vec3.fromValues(-1, -1, -1));
 }
 
+// Sample a neighborhood of pixels weighted by a convolution kernel, instead of
+// a single pixel. `kernel` may be one of the presets `'blur'`, `'sharpen'`,
+// `'edgeDetect'`, or `'emboss'`, an array of 9 weights for a 3×3 kernel, or an
+// array of 25 weights for a 5×5 kernel. Weights are given row by row, from top
+// to bottom. Pass `null` to go back to sampling a single pixel.
+//
+// ```
+// x();
+// render();
+// identity();
+// kernel('blur');
+// times(8);
+// render();
+// ```
+function kernel(kernel) {
+  if (Array.isArray(kernel)) {
+    state.filter.kernel = { custom: kernel };
+  } else {
+    state.filter.kernel = kernel;
+  }
+}
+
+// Transform and blend colors in linear light. Normally, colors are
+// transformed and blended as they are stored, in the sRGB encoding, which
+// is not perceptually correct. If `linear` is `true`, colors are decoded to
+// linear light before the color transform and alpha blending are applied,
+// and encoded back to sRGB afterwards.
+//
+// ```
+// linear(true);
+// rotateColor('green', 0.1 * TAU);
+// alpha(0.5);
+// render();
+// ```
+function linear(linear) {
+  state.filter.linear = linear ?? true;
+}
+
 // Field that covers pixels where the pixel's index mod `divisor` is equal to `remainder`.
 //
 // ```
@@ -365,6 +593,39 @@ function radio(name, options) {
   return widgets['radio-' + name] ?? options[0];
 }
 
+// Run a Gray-Scott reaction-diffusion simulation, advancing it by one step
+// each time the filter is applied. The simulation is seeded from the
+// canvas the first time it is rendered, with chemical B placed where the
+// canvas is bright. `feed` and `kill` control the pattern that emerges, and
+// `diffusionA` and `diffusionB` are the diffusion rates of the two chemicals.
+// The concentration of A minus B is output as a grayscale value, before the
+// color transform is applied. Pass `null` to disable. Requires support for
+// the `EXT_color_buffer_float` WebGL extension.
+//
+// ```
+// circle();
+// render();
+// identity();
+// all();
+// reactionDiffusion(0.055, 0.062);
+// times(16);
+// while(true) {
+//   await render();
+// }
+// ```
+function reactionDiffusion(feed, kill, diffusionA, diffusionB) {
+  if (feed === null) {
+    state.filter.reactionDiffusion = null;
+  } else {
+    state.filter.reactionDiffusion = {
+      diffusionA: diffusionA ?? 1.0,
+      diffusionB: diffusionB ?? 0.5,
+      feed: feed ?? 0.055,
+      kill: kill ?? 0.062,
+    };
+  }
+}
+
 // Reset the image filter and clear the canvas.
 // ```
 // x();
@@ -410,10 +671,76 @@ function reset() {
   state.filter = new Filter();
 }
 
+// Set the palette used by `gradientMap`. `colors` may be an array of
+// `[r, g, b]` colors, with components from 0 to 1, which are spaced evenly,
+// like a row of pixels from an image, or an array of `[position, [r, g, b]]`
+// color stops, with positions from 0 to 1. Colors between stops are
+// interpolated. The default palette is a ramp from black to white.
+//
+// ```
+// palette([
+//   [0.0, [0, 0, 0]],
+//   [0.8, [1, 0, 0.5]],
+//   [1.0, [1, 1, 1]],
+// ]);
+// x();
+// gradientMap('luminance');
+// render();
+// ```
+function palette(colors) {
+  if (colors.every((color) => Array.isArray(color[1]))) {
+    self.postMessage(JSON.stringify({ palette: { stops: colors } }));
+  } else {
+    self.postMessage(JSON.stringify({ palette: { colors } }));
+  }
+}
+
+// Reduce the number of levels of each color channel to `levels`, after the
+// color transform. `levels` may be a number, or an array of levels for the
+// red, green, and blue channels. Pass `null` to disable.
+//
+// ```
+// identity();
+// posterize([4, 4, 2]);
+// render();
+// ```
+function posterize(levels) {
+  if (levels === null) {
+    state.filter.posterize = null;
+  } else if (Array.isArray(levels)) {
+    state.filter.posterize = levels;
+  } else {
+    state.filter.posterize = [levels, levels, levels];
+  }
+}
+
+// Set the precision of the canvas. Normally, each color channel is stored
+// as an 8-bit value, so long chains of filters can show banding. Valid
+// values for `precision` are:
+//
+// - `'byte'`: 8-bit channels. This is the default.
+// - `'half'`: 16-bit floating point channels.
+// - `'float'`: 32-bit floating point channels.
+//
+// Floating point precision is not supported on all devices, in which case
+// byte precision is used. Changing precision clears the canvas.
+//
+// ```
+// precision('half');
+// identity();
+// scale(0.99);
+// times(256);
+// render();
+// ```
+function precision(precision) {
+  self.postMessage(JSON.stringify({ precision }));
+}
+
 // Set resolution to a fixed value. Normally, the resolution increases and
 // decreases automatically as the window is resized. This is usually what you
 // want, but it is convenient to override it if you want to render at a fixed
-// size, for example for saving high-resolution images:
+// size, for example for saving high-resolution images, or for rendering
+// low-resolution images, which can be scaled to the window using `scaling`:
 //
 // ```
 // resolution(4096);
@@ -464,6 +791,57 @@ function rotate(rotation) {
   transform(rotation, [1.0, 1.0], [0.0, 0.0]);
 }
 
+// Show the canvas as it was `frames` frames ago. `rewind(0)` returns to
+// showing the current canvas. Requires first enabling the rewind buffer with
+// `rewindCapacity`. Filters continue to be rendered while rewinding, but are
+// not shown, and no new frames are added to the rewind buffer.
+//
+// ```
+// rewindCapacity(256);
+// x();
+// scale(0.99);
+// while(true) {
+//   rewind(slider('rewind', 0, 255, 1, 0));
+//   if (checkbox('restore')) {
+//     restoreRewind();
+//   }
+//   await render();
+// }
+// ```
+function rewind(frames) {
+  self.postMessage(JSON.stringify({ rewind: frames }));
+}
+
+// Set the number of past frames kept in the rewind buffer. One frame is added
+// to the rewind buffer each time the canvas is rendered to and displayed.
+// Defaults to zero, which disables the rewind buffer. Each frame is stored on
+// the GPU at the current resolution, so large capacities can use a lot of
+// memory. Changing the resolution empties the rewind buffer.
+//
+// ```
+// rewindCapacity(60);
+// ```
+function rewindCapacity(capacity) {
+  self.postMessage(JSON.stringify({ rewindCapacity: capacity }));
+}
+
+// Replace the canvas with the frame currently shown by `rewind`, `stepBack`,
+// or `stepForward`, and return to showing the canvas.
+//
+// ```
+// rewindCapacity(60);
+// x();
+// scale(0.99);
+// for (let i = 0; i < 60; i++) {
+//   await render();
+// }
+// rewind(30);
+// restoreRewind();
+// ```
+function restoreRewind() {
+  self.postMessage(JSON.stringify('restoreRewind'));
+}
+
 // Field that covers pixels where `pixel.y % (nrows + step) < nrows`. Will cover `nrows` pixels and then
 // skip `step` pixels.
 //
@@ -483,8 +861,36 @@ function rows(on, off) {
 // render();
 // save();
 // ```
-function save() {
-  self.postMessage(JSON.stringify('save'));
+//
+// If `resolution` is given, save a `resolution` by `resolution` PNG instead,
+// by replaying every filter rendered since the canvas was last cleared in
+// tiles. This allows saving images larger than the largest canvas supported
+// by the GPU. Each tile is rendered with an overlapping margin, so filters
+// that move pixels by less than the margin produce seamless images. Saving
+// fails if filters move pixels further than the margin allows, which can
+// happen with large scales and rotations, or many repetitions of smaller
+// ones. Audio data is not recorded, so fields that depend on audio will use
+// the current audio data. `resolution` may be at most 16384, and large images
+// use a lot of memory.
+//
+// Tiled images can only be saved if every filter since the canvas was last
+// cleared can be replayed. Saving fails after rendering with `delay`,
+// `reactionDiffusion`, `sort`, or `wrap`, after changing the palette, and
+// after restoring a rewound frame.
+//
+// ```
+// circle();
+// scale(0.999);
+// times(4);
+// render();
+// save(8192);
+// ```
+function save(resolution) {
+  if (resolution === undefined) {
+    self.postMessage(JSON.stringify('save'));
+  } else {
+    self.postMessage(JSON.stringify({ saveTiled: resolution }));
+  }
 }
 
 // Seed RNG with `n`.
@@ -513,6 +919,28 @@ function scale(scale) {
   transform(0, [scale, scale], [0.0, 0.0]);
 }
 
+// Set how the canvas is scaled to fit the window. This only affects how the
+// canvas is displayed, and is mostly useful in combination with `resolution`.
+// Valid values for `mode` are:
+//
+// - `'fill'`: Scale the canvas to cover the window, cropping the edges. This
+//   is the default.
+// - `'fit'`: Scale the canvas to fit inside the window, with black bars on
+//   the sides.
+// - `'stretch'`: Stretch the canvas to the size of the window.
+// - `'integer'`: Scale the canvas by the largest whole number that fits inside
+//   the window, so that every pixel is the same size.
+//
+// ```
+// resolution(64);
+// scaling('integer');
+// x();
+// render();
+// ```
+function scaling(mode) {
+  self.postMessage(JSON.stringify({ scaling: mode }));
+}
+
 // Return a promise that resolves after `ms` milliseconds.
 //
 // ```
@@ -557,6 +985,56 @@ function slider(name, min, max, step, initial) {
   return widgets['slider-' + name] ?? initial;
 }
 
+// Take a snapshot of the current canvas. If the browser loses the GPU context,
+// for example after a GPU reset, the canvas will be restored from the most
+// recent snapshot or saved image when the context is restored, as long as the
+// resolution has not changed. Snapshots are stored in memory and not
+// downloaded.
+//
+// ```
+// x();
+// render();
+// snapshot();
+// ```
+function snapshot() {
+  self.postMessage(JSON.stringify('snapshot'));
+}
+
+// Show the frame before the one currently shown from the rewind buffer. See
+// `rewind`.
+function stepBack() {
+  self.postMessage(JSON.stringify('stepBack'));
+}
+
+// Show the frame after the one currently shown from the rewind buffer. See
+// `rewind`.
+function stepForward() {
+  self.postMessage(JSON.stringify('stepForward'));
+}
+
+// Sort pixels by luminance, moving bright pixels towards `direction`, which
+// may be `'up'`, `'down'`, `'left'`, or `'right'`. Only runs of pixels
+// brighter than `threshold` are sorted. Each time the filter is applied,
+// pixels move at most one step, so use `times` or render repeatedly to sort
+// progressively. Pass `null` to disable.
+//
+// ```
+// identity();
+// sort('right', 0.25);
+// times(64);
+// render();
+// ```
+function sort(direction, threshold) {
+  if (direction === null) {
+    state.filter.sort = null;
+  } else {
+    state.filter.sort = {
+      direction: direction ?? 'right',
+      threshold: threshold ?? 0.25,
+    };
+  }
+}
+
 // A square field.
 //
 // ```
@@ -572,6 +1050,19 @@ function timeDomain() {
   state.filter.field = 'TimeDomain';
 }
 
+// Set each color channel to 1 if it is above `threshold`, and 0 otherwise,
+// after the color transform. Pass `null` to disable.
+//
+// ```
+// identity();
+// threshold(0.5);
+// dither('blueNoise');
+// render();
+// ```
+function threshold(threshold) {
+  state.filter.threshold = threshold === undefined ? 0.5 : threshold;
+}
+
 // Execute the filter `times` times.
 function times(times) {
   state.filter.times = times;
@@ -680,14 +1171,27 @@ class Rng {
 class Filter {
   constructor() {
     this.alpha = 1.0;
+    this.automaton = null;
+    this.blendMode = 'normal';
+    this.colorOps = [];
     this.colorTransform = mat4.fromScaling(
       mat4.create(),
       vec3.fromValues(-1, -1, -1)
     );
     this.positionTransform = mat3.create();
     this.coordinates = false;
+    this.curves = null;
     this.defaultColor = [0.0, 0.0, 0.0];
+    this.delay = 0;
+    this.dither = null;
     this.field = 'All';
+    this.gradientMap = null;
+    this.kernel = null;
+    this.linear = false;
+    this.posterize = null;
+    this.reactionDiffusion = null;
+    this.sort = null;
+    this.threshold = null;
     this.times = 1;
     this.wrap = false;
   }